@@ -1,30 +1,106 @@
-use crate::client::SynoError::{Api, Auth, Configuration, InvalidInput, InvalidResponse};
+mod bt;
+mod create;
+mod legacy;
+mod list;
+mod package;
+mod rss;
+mod search;
+mod settings;
+mod statistics;
+mod wait;
+mod watch;
+
+use crate::client::Backend::{DownloadStation2, Legacy};
+use crate::client::SynoError::{
+    Api, ApiNotAvailable, Auth, Configuration, DestinationDenied, DestinationNotFound, Http,
+    InvalidInput, InvalidResponse, Network, OtpEnforced, OtpInvalid, OtpRequired, TaskFailed,
+};
+use crate::client::legacy::LEGACY_TASK_API;
 use crate::entities::TaskStatus::Finished;
 use crate::entities::{
-    AuthData, SynologyResponse, TaskCompleted, TaskCreated, TaskInfo, TaskOperation, Tasks,
+    AdditionalFields, ApiInfo, AuthData, BatchResult, SynologyResponse, TaskCompleted, TaskCreated,
+    TaskInfo, TaskOperation, TaskStatus, Tasks,
 };
+use crate::error_code::ErrorCode;
+use crate::session::{Session, SessionStore};
 use anyhow::{Context, Result};
-use log::debug;
-use reqwest::multipart::Part;
+use chrono::{DateTime, TimeDelta, Utc};
+use log::{debug, warn};
 use reqwest::{Client, multipart};
+use std::collections::HashMap;
 use std::env;
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
-const API_PATH: &str = "/webapi/entry.cgi";
+pub use bt::{TaskLimitsUpdate, TrackerReplacement};
+pub use create::CreateTaskOptions;
+pub use list::{ListTasksRequest, TaskPageStream, TaskPages};
+pub use search::BtSearch;
+pub use wait::WaitForCompletion;
+pub use watch::{TaskEvent, TaskEventStream, TaskWatcher};
+
+const WEBAPI_PATH: &str = "/webapi";
+
+/// Path of the `SYNO.API.Info` endpoint, relative to [`WEBAPI_PATH`]. Available on
+/// DSM 6 and DSM 7, before the paths of the other APIs are known.
+const INFO_PATH: &str = "query.cgi";
+
+/// Path of `SYNO.API.Auth` before the `SYNO.API.Info` response is known, used to log
+/// out on drop without querying it
+const DEFAULT_AUTH_PATH: &str = "entry.cgi";
 
 const SESSION_EXPIRED_CODE: i32 = 119;
 
+const OTP_REQUIRED_CODE: i32 = 403;
+const OTP_INVALID_CODE: i32 = 404;
+const OTP_ENFORCED_CODE: i32 = 406;
+
+const DEFAULT_DEVICE_NAME: &str = "syno-download-station";
+
 /// Custom error types for the [`SynoDS`] client
 #[non_exhaustive]
 #[derive(Error, Debug)]
 pub enum SynoError {
-    #[error("Authentication error: {message}")]
+    #[error("Authentication error: {message}{}", auth_code_details(*code))]
     Auth { code: Option<i32>, message: String },
 
-    #[error("Synology API error: code={code}, message={message}")]
-    Api { code: i32, message: String },
+    #[error("Authentication error: 2-factor authentication code required")]
+    OtpRequired,
+
+    #[error("Authentication error: 2-factor authentication code rejected")]
+    OtpInvalid,
+
+    #[error("Authentication error: 2-factor authentication must be set up for this account")]
+    OtpEnforced,
+
+    /// Error code returned by the API named `api`, decoded with [`ErrorCode::for_api`]
+    #[error("Synology API error: code={code} ({}), message={message}", ErrorCode::for_api(api, *code))]
+    Api {
+        api: String,
+        code: i32,
+        message: String,
+    },
+
+    /// The destination folder does not exist, see [`TaskStatus::ErrorDestNoExist`]
+    #[error("Destination does not exist: {destination}")]
+    DestinationNotFound { destination: String },
+
+    /// No permission to write to the destination folder, see [`TaskStatus::ErrorDestDeny`]
+    #[error("Destination denied: {destination}")]
+    DestinationDenied { destination: String },
+
+    #[error("HTTP error: status={status}, message={message}")]
+    Http { status: u16, message: String },
+
+    #[error("API not available: {api}, {message}")]
+    ApiNotAvailable { api: String, message: String },
+
+    /// The Download Station package is stopped, so the NAS doesn't provide `api`, see
+    /// [`SynoDS::get_service_status`] and [`SynoDS::start_service`]
+    #[error("Download Station is not running: {api} is not available")]
+    ServiceNotRunning { api: String },
 
     #[error("Network request error: {0}")]
     Network(#[from] reqwest::Error),
@@ -47,21 +123,237 @@ pub enum SynoError {
     #[error("Task modification failed: {0}")]
     TaskModification(String),
 
+    /// A waited for task entered an error status, see [`SynoDS::wait_for_completion`]
+    #[error("Task {task_id} failed: status={status}{}", error_detail_suffix(error_detail.as_deref()))]
+    TaskFailed {
+        task_id: String,
+        status: TaskStatus,
+        error_detail: Option<String>,
+    },
+
+    /// A waited for task no longer exists, see [`SynoDS::wait_for_completion`]
+    #[error("Task not found: {0}")]
+    TaskNotFound(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
     #[error("Configuration error: {0}")]
     Configuration(String),
 }
 
+impl SynoError {
+    /// Decodes the Synology error code carried by the error, if any
+    #[must_use]
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Auth {
+                code: Some(code), ..
+            } => Some(ErrorCode::auth(*code)),
+            Api { api, code, .. } => Some(ErrorCode::for_api(api, *code)),
+            OtpRequired => Some(ErrorCode::OtpRequired),
+            OtpInvalid => Some(ErrorCode::OtpInvalid),
+            OtpEnforced => Some(ErrorCode::OtpEnforced),
+            DestinationNotFound { .. } => Some(ErrorCode::DestinationNotFound),
+            DestinationDenied { .. } => Some(ErrorCode::DestinationDenied),
+            _ => None,
+        }
+    }
+
+    /// Task status with the same meaning as the error, for destination errors and
+    /// failed tasks
+    #[must_use]
+    pub fn task_status(&self) -> Option<TaskStatus> {
+        match self {
+            DestinationNotFound { .. } => Some(TaskStatus::ErrorDestNoExist),
+            DestinationDenied { .. } => Some(TaskStatus::ErrorDestDeny),
+            TaskFailed { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Whether repeating the request may succeed, e.g. when the NAS is busy,
+    /// unreachable or the session has expired
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Http { status, .. } => *status == 429 || *status >= 500,
+            Network(error) => error.is_timeout() || error.is_connect(),
+            _ => self.error_code().is_some_and(|code| code.is_retryable()),
+        }
+    }
+
+    /// Whether the error is caused by missing permissions
+    #[must_use]
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            Http { status, .. } => *status == 401 || *status == 403,
+            _ => self
+                .error_code()
+                .is_some_and(|code| code.is_permission_denied()),
+        }
+    }
+}
+
+/// Formats the code of an authentication error together with its description
+fn auth_code_details(code: Option<i32>) -> String {
+    code.map(|code| format!(", code={code} ({})", ErrorCode::auth(code)))
+        .unwrap_or_default()
+}
+
+/// Whether a `SYNO.API.Auth` error code means the session has already ended on the NAS
+fn session_ended(code: i32) -> bool {
+    matches!(
+        ErrorCode::auth(code),
+        ErrorCode::SessionTimeout | ErrorCode::SessionInterrupted | ErrorCode::InvalidSession
+    )
+}
+
+/// Formats the error detail of a failed task
+fn error_detail_suffix(error_detail: Option<&str>) -> String {
+    error_detail
+        .map(|detail| format!(", detail={detail}"))
+        .unwrap_or_default()
+}
+
+/// Synology Web API used by the client, together with the range of versions the client can speak
+#[derive(Debug, Clone, Copy)]
+struct ApiSpec {
+    name: &'static str,
+    min_version: u32,
+    max_version: u32,
+}
+
+const INFO_API: ApiSpec = ApiSpec {
+    name: "SYNO.API.Info",
+    min_version: 1,
+    max_version: 1,
+};
+
+const AUTH_API: ApiSpec = ApiSpec {
+    name: "SYNO.API.Auth",
+    min_version: 2,
+    max_version: 7,
+};
+
+const TASK_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task",
+    min_version: 1,
+    max_version: 2,
+};
+
+const TASK_COMPLETE_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.Complete",
+    min_version: 1,
+    max_version: 1,
+};
+
+/// Formats a string as a JSON string value, as expected by the APIs with the JSON
+/// request format
+fn json_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// Error code of `SYNO.DownloadStation2.Task` for a destination without write permission
+const DESTINATION_DENIED_CODE: i32 = 402;
+
+/// Error code of `SYNO.DownloadStation2.Task` for a destination that does not exist
+const DESTINATION_NOT_FOUND_CODE: i32 = 403;
+
+/// Checks that a destination is a usable shared folder path
+fn validate_destination(destination: &str) -> Result<()> {
+    if destination.trim_matches('/').is_empty() {
+        return Err(InvalidInput("Destination path cannot be empty".into()).into());
+    }
+
+    if destination.chars().any(char::is_control) {
+        return Err(
+            InvalidInput("Destination path cannot contain control characters".into()).into(),
+        );
+    }
+
+    if destination.split('/').any(|component| component == "..") {
+        return Err(InvalidInput(format!(
+            "Destination path cannot contain '..', got: {destination}"
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Formats indexes as a JSON array
+fn json_index_list(indexes: &[u32]) -> String {
+    let indexes = indexes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{indexes}]")
+}
+
+/// Family of Download Station APIs used to manage tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// `SYNO.DownloadStation2.*` APIs, provided by Download Station on DSM 7
+    DownloadStation2,
+    /// Legacy `SYNO.DownloadStation.Task` API, provided by Download Station on older DSM versions
+    Legacy,
+}
+
+/// Lifecycle information about the current session
+#[derive(Debug, Clone, Copy)]
+pub struct SessionInfo {
+    /// When the session was obtained
+    pub logged_in_at: DateTime<Utc>,
+    /// When the session was last used for a request
+    pub last_used_at: DateTime<Utc>,
+}
+
+impl SessionInfo {
+    /// Time elapsed since the session was obtained
+    #[must_use]
+    pub fn age(&self) -> TimeDelta {
+        Utc::now() - self.logged_in_at
+    }
+
+    /// Time elapsed since the session was last used
+    #[must_use]
+    pub fn idle_time(&self) -> TimeDelta {
+        Utc::now() - self.last_used_at
+    }
+}
+
+/// Session the client is currently authorized with
+struct ActiveSession {
+    session: Session,
+    last_used_at: DateTime<Utc>,
+}
+
+/// Path and negotiated version of an API, resolved from the `SYNO.API.Info` response
+struct ResolvedApi {
+    path: String,
+    version: String,
+}
+
 /// Synology Download Station client
 pub struct SynoDS {
     url: String,
     username: String,
     password: String,
     client: Client,
-    sid: RwLock<String>,
+    session: RwLock<Option<ActiveSession>>,
+    session_store: Option<Arc<dyn SessionStore>>,
+    logout_on_drop: bool,
+    api_info: RwLock<Option<Arc<HashMap<String, ApiInfo>>>>,
+    backend: Option<Backend>,
+    device_name: String,
+    device_id: RwLock<Option<String>>,
 }
 
-const DEFAULT_PARAMS: &[(&str, &str)] = &[("api", "SYNO.DownloadStation2.Task"), ("version", "2")];
-
 impl SynoDS {
     /// Creates a new `SynoDS` client with the given url, credentials and timeout
     ///
@@ -103,7 +395,13 @@ impl SynoDS {
             username,
             password,
             client,
-            sid: RwLock::new(String::new()),
+            session: RwLock::new(None),
+            session_store: None,
+            logout_on_drop: false,
+            api_info: RwLock::new(None),
+            backend: None,
+            device_name: DEFAULT_DEVICE_NAME.into(),
+            device_id: RwLock::new(None),
         })
     }
 
@@ -123,39 +421,111 @@ impl SynoDS {
 
     /// Authorizes the client by getting a session ID
     ///
+    /// If a device ID is known (set with [`SynoDSBuilder::device_id`] or received from
+    /// [`SynoDS::authorize_with_otp`]), it is sent along so that accounts with 2-factor
+    /// authentication can log in without a fresh OTP code.
+    ///
     /// # Errors
     ///
     /// Returns an error if:
     /// - Network request fails
     /// - Authentication fails
+    /// - Account requires a 2-factor authentication code ([`SynoError::OtpRequired`])
     /// - Response cannot be parsed
     pub async fn authorize(&self) -> Result<()> {
-        let params = [
-            ("api", "SYNO.API.Auth"),
-            ("version", "7"),
-            ("method", "login"),
-            ("account", &self.username),
-            ("passwd", &self.password),
-            ("format", "sid"),
-        ];
+        self.login(None, false).await
+    }
+
+    /// Authorizes the client with a 2-factor authentication (OTP) code
+    ///
+    /// When `remember_device` is set, the NAS is asked to trust this client and returns a
+    /// device ID, available afterwards through [`SynoDS::device_id`]. Storing it and passing
+    /// it to [`SynoDSBuilder::device_id`] allows later logins without an OTP code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - OTP code is empty
+    /// - Network request fails
+    /// - Authentication fails
+    /// - OTP code is rejected ([`SynoError::OtpInvalid`])
+    /// - Response cannot be parsed
+    pub async fn authorize_with_otp(&self, otp_code: &str, remember_device: bool) -> Result<()> {
+        if otp_code.is_empty() {
+            return Err(InvalidInput("OTP code cannot be empty".into()).into());
+        }
+
+        self.login(Some(otp_code), remember_device).await
+    }
+
+    /// Gets the device ID used for trusted-device login, if known
+    pub async fn device_id(&self) -> Option<String> {
+        self.device_id.read().await.clone()
+    }
+
+    /// Logs in with the configured credentials, optionally with an OTP code,
+    /// and stores the session ID and the device ID returned by the NAS
+    async fn login(&self, otp_code: Option<&str>, enable_device_token: bool) -> Result<()> {
+        let auth_api = self.resolve_api(&AUTH_API).await?;
+        let device_id = self.device_id.read().await.clone();
+        let params = {
+            let mut params = vec![
+                ("api", AUTH_API.name),
+                ("version", auth_api.version.as_str()),
+                ("method", "login"),
+                ("account", self.username.as_str()),
+                ("passwd", self.password.as_str()),
+                ("format", "sid"),
+            ];
+            if let Some(otp_code) = otp_code {
+                params.push(("otp_code", otp_code));
+            }
+            if enable_device_token {
+                params.push(("enable_device_token", "yes"));
+                params.push(("device_name", self.device_name.as_str()));
+            }
+            if let Some(device_id) = &device_id {
+                params.push(("device_id", device_id.as_str()));
+            }
+            params
+        };
 
         let response = self
-            .make_raw_request::<SynologyResponse<AuthData>>(&params)
+            .make_raw_request::<SynologyResponse<AuthData>>(&auth_api.path, &params)
             .await
             .context("Failed to authorize")?;
 
         if response.success {
             match response.data {
                 Some(data) => {
-                    *self.sid.write().await = data.sid;
+                    if !data.device_id.is_empty() {
+                        *self.device_id.write().await = Some(data.device_id);
+                    }
+                    let session = Session {
+                        sid: data.sid,
+                        synotoken: data.synotoken,
+                        device_id: self.device_id.read().await.clone(),
+                        obtained_at: Utc::now(),
+                    };
+                    if let Some(session_store) = &self.session_store {
+                        if let Err(error) = session_store.save(&session) {
+                            warn!("Failed to save session: {error:#}");
+                        }
+                    }
+                    self.set_session(session).await;
                     Ok(())
                 }
                 None => Err(InvalidResponse("No data received".into()).into()),
             }
         } else if let Some(error) = response.error {
-            Err(Auth {
-                code: Some(error.code),
-                message: "Authentication failed".into(),
+            Err(match error.code {
+                OTP_REQUIRED_CODE => OtpRequired,
+                OTP_INVALID_CODE => OtpInvalid,
+                OTP_ENFORCED_CODE => OtpEnforced,
+                code => Auth {
+                    code: Some(code),
+                    message: "Authentication failed".into(),
+                },
             }
             .into())
         } else {
@@ -168,271 +538,432 @@ impl SynoDS {
     }
 
     pub async fn is_authorized(&self) -> bool {
-        !self.sid.read().await.is_empty()
+        self.session.read().await.is_some()
     }
 
-    /// Gets all Download Station tasks
+    /// Exports the current session, if authorized
     ///
-    /// # Errors
-    ///
-    /// Returns an error if:
-    /// - Network request fails
-    /// - API returns an error response
-    /// - Response cannot be parsed
-    /// - Session is invalid or expired
-    pub async fn get_tasks(&self) -> Result<Tasks> {
-        let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "list"));
-            params.push((
-                "additional",
-                r#"["transfer","tracker","peer","file","detail"]"#,
-            ));
-            params
-        };
+    /// The session can be persisted and passed to [`SynoDSBuilder::session`] on the next
+    /// start to avoid logging in again. See also [`SynoDSBuilder::session_store`].
+    pub async fn export_session(&self) -> Option<Session> {
+        self.session
+            .read()
+            .await
+            .as_ref()
+            .map(|active| active.session.clone())
+    }
 
-        let response = self
-            .make_api_request::<Tasks>(&all_params)
+    /// Replaces the current session
+    async fn set_session(&self, session: Session) {
+        *self.session.write().await = Some(ActiveSession {
+            last_used_at: session.obtained_at,
+            session,
+        });
+    }
+
+    /// Gets the ID of the current session, empty if not authorized
+    async fn sid(&self) -> String {
+        self.session
+            .read()
             .await
-            .context("Failed to get tasks")?;
+            .as_ref()
+            .map(|active| active.session.sid.clone())
+            .unwrap_or_default()
+    }
 
-        if response.success {
-            match response.data {
-                Some(tasks) => Ok(tasks),
-                None => Err(InvalidResponse("No data received".into()).into()),
-            }
-        } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: "Failed to get tasks".into(),
-            }
-            .into())
-        } else {
-            Err(InvalidResponse("Failed to get tasks, unknown error".into()).into())
-        }
+    /// Gets lifecycle information about the current session, if authorized
+    ///
+    /// Allows callers to re-authorize proactively, e.g. when the session is older than
+    /// the session timeout configured on the NAS, instead of relying on the transparent
+    /// re-authorization on session expiry.
+    pub async fn session_info(&self) -> Option<SessionInfo> {
+        self.session
+            .read()
+            .await
+            .as_ref()
+            .map(|active| SessionInfo {
+                logged_in_at: active.session.obtained_at,
+                last_used_at: active.last_used_at,
+            })
     }
 
-    /// Gets detailed information about specific task(s)
+    /// Ends the current session on the NAS
+    ///
+    /// Does nothing if the client is not authorized. Once the NAS has ended the session,
+    /// or reports it as already invalid, the session is forgotten locally and removed from
+    /// the session store if one is configured. If the logout fails otherwise, the session
+    /// is kept so that logging out can be retried.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - IDs vector is empty
     /// - Network request fails
     /// - API returns an error response
     /// - Response cannot be parsed
-    /// - Session is invalid or expired
-    pub async fn get_task(&self, ids: Vec<String>) -> Result<TaskInfo> {
-        if ids.is_empty() {
-            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+    pub async fn logout(&self) -> Result<()> {
+        let sid = self.sid().await;
+        if sid.is_empty() {
+            return Ok(());
         }
 
-        let id_string = ids.join(",");
-        let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "get"));
-            params.push(("id", &id_string));
-            params.push((
-                "additional",
-                r#"["transfer","tracker","peer","file","detail"]"#,
-            ));
-            params
-        };
+        let auth_api = self.resolve_api(&AUTH_API).await?;
+        let params = [
+            ("api", AUTH_API.name),
+            ("version", &auth_api.version),
+            ("method", "logout"),
+            ("_sid", &sid),
+        ];
 
         let response = self
-            .make_api_request::<TaskInfo>(&all_params)
+            .make_raw_request::<SynologyResponse<()>>(&auth_api.path, &params)
             .await
-            .context("Failed to get task details")?;
+            .context("Failed to log out")?;
 
         if response.success {
-            match response.data {
-                Some(task_info) => Ok(task_info),
-                None => Err(InvalidResponse("No data received".into()).into()),
-            }
+            debug!("Successfully logged out");
+            self.forget_session().await;
+            Ok(())
         } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: "Failed to get task".into(),
+            if session_ended(error.code) {
+                debug!(
+                    "Session already ended on the NAS (error code {})",
+                    error.code
+                );
+                self.forget_session().await;
+                return Ok(());
+            }
+            Err(Auth {
+                code: Some(error.code),
+                message: "Logout failed".into(),
             }
             .into())
         } else {
-            Err(InvalidResponse("Failed to get task, unknown error".into()).into())
+            Err(Auth {
+                code: None,
+                message: "Logout failed, unknown error".into(),
+            }
+            .into())
         }
     }
 
-    /// Creates a new download task from a URI (HTTP/HTTPS URL or magnet link)
+    /// Forgets the current session and removes it from the session store
+    async fn forget_session(&self) {
+        self.session.write().await.take();
+        if let Some(session_store) = &self.session_store {
+            if let Err(error) = session_store.clear() {
+                warn!("Failed to clear stored session: {error:#}");
+            }
+        }
+    }
+
+    /// Ends the current session on the NAS and consumes the client
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - URI or destination is empty
-    /// - URI doesn't start with http://, https://, or magnet:
     /// - Network request fails
     /// - API returns an error response
     /// - Response cannot be parsed
-    pub async fn create_task(&self, uri: &str, destination: &str) -> Result<()> {
-        // Validate input parameters
-        if uri.is_empty() {
-            return Err(InvalidInput("URI cannot be empty".into()).into());
-        }
+    pub async fn close(self) -> Result<()> {
+        self.logout().await
+    }
 
-        if destination.is_empty() {
-            return Err(InvalidInput("Destination path cannot be empty".into()).into());
+    /// Gets the APIs provided by the NAS, as reported by `SYNO.API.Info`
+    ///
+    /// The information is queried on first use and cached. Every other method uses it to
+    /// find the path of its API and to pick the highest version supported by both the
+    /// client and the NAS. The cache is refreshed once when an API is missing from it or
+    /// the NAS answers with error code 102 or 104, for example after a package update,
+    /// see also [`SynoDS::refresh_api_info`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    pub async fn api_info(&self) -> Result<Arc<HashMap<String, ApiInfo>>> {
+        if let Some(api_info) = self.api_info.read().await.as_ref() {
+            return Ok(Arc::clone(api_info));
         }
 
-        // Basic URI validation
-        if !uri.starts_with("http://")
-            && !uri.starts_with("https://")
-            && !uri.starts_with("magnet:")
-        {
-            return Err(InvalidInput(format!(
-                "URI must start with http://, https://, or magnet:, got: {uri}"
-            ))
-            .into());
+        let mut cached = self.api_info.write().await;
+        if let Some(api_info) = cached.as_ref() {
+            return Ok(Arc::clone(api_info));
         }
+        let api_info = Arc::new(self.query_api_info().await?);
+        *cached = Some(Arc::clone(&api_info));
+        Ok(api_info)
+    }
 
-        debug!("Creating download task. URI: {uri}, Destination: {destination}");
+    /// Queries `SYNO.API.Info` again and replaces the cached API info
+    ///
+    /// Useful after installing, updating or starting a package on the NAS while the
+    /// client is in use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    pub async fn refresh_api_info(&self) -> Result<Arc<HashMap<String, ApiInfo>>> {
+        debug!("Refreshing API info");
+        let api_info = Arc::new(self.query_api_info().await?);
+        *self.api_info.write().await = Some(Arc::clone(&api_info));
+        Ok(api_info)
+    }
 
-        // Parameters for the create task API call
-        let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "create"));
-            params.push(("type", "\"url\""));
-            params.push(("destination", destination));
-            params.push(("url", uri));
-            params.push(("create_list", "false"));
-            params
-        };
+    /// Queries `SYNO.API.Info` for all APIs available on the NAS
+    async fn query_api_info(&self) -> Result<HashMap<String, ApiInfo>> {
+        let version = INFO_API.max_version.to_string();
+        let params = [
+            ("api", INFO_API.name),
+            ("version", &version),
+            ("method", "query"),
+            ("query", "all"),
+        ];
 
-        // Use the make_api_request method to create the task via POST request
         let response = self
-            .make_api_request::<TaskCreated>(&all_params)
+            .make_raw_request::<SynologyResponse<HashMap<String, ApiInfo>>>(INFO_PATH, &params)
             .await
-            .context("Failed to create download task")?;
+            .context("Failed to query API info")?;
 
         if response.success {
-            debug!("Successfully created download task for URI: {uri}");
-            Ok(())
+            match response.data {
+                Some(api_info) => Ok(api_info),
+                None => Err(InvalidResponse("No data received".into()).into()),
+            }
         } else if let Some(error) = response.error {
             Err(Api {
+                api: INFO_API.name.into(),
                 code: error.code,
-                message: "Failed to create task".into(),
+                message: "Failed to query API info".into(),
             }
             .into())
         } else {
-            Err(InvalidResponse("Failed to create task, unknown error".into()).into())
+            Err(InvalidResponse("Failed to query API info, unknown error".into()).into())
         }
     }
 
-    /// Creates a new download task from a torrent file
-    /// Uses multipart/form-data with POST for file uploads
+    /// Gets the backend used to manage tasks
+    ///
+    /// Unless set explicitly with [`SynoDSBuilder::backend`], the backend is detected from
+    /// [`SynoDS::api_info`]: `DownloadStation2` is preferred and the legacy API is used
+    /// only when the NAS doesn't provide `SYNO.DownloadStation2.Task`.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - File data is empty
-    /// - File name or destination is empty
-    /// - Network request fails
-    /// - API returns an error response
-    /// - Response cannot be parsed
-    /// - Torrent file is invalid or corrupted
-    pub async fn create_task_from_file(
-        &self,
-        file_data: &[u8],
-        file_name: &str,
-        destination: &str,
-    ) -> Result<()> {
-        // Validate input parameters
-        if file_data.is_empty() {
-            return Err(InvalidInput("File data cannot be empty".into()).into());
+    /// - API info cannot be queried
+    /// - Neither of the task APIs is provided by the NAS
+    pub async fn backend(&self) -> Result<Backend> {
+        if let Some(backend) = self.backend {
+            return Ok(backend);
         }
 
-        if file_name.is_empty() {
-            return Err(InvalidInput("File name cannot be empty".into()).into());
+        let cached = self.api_info.read().await.is_some();
+        let mut api_info = self.api_info().await?;
+        if cached && Self::detect_backend(&api_info).is_none() {
+            api_info = self.refresh_api_info().await?;
+        }
+        if let Some(backend) = Self::detect_backend(&api_info) {
+            return Ok(backend);
         }
 
-        if destination.is_empty() {
-            return Err(InvalidInput("Destination path cannot be empty".into()).into());
+        self.ensure_service_running(TASK_API.name).await?;
+        Err(ApiNotAvailable {
+            api: TASK_API.name.into(),
+            message: "no Download Station task API is provided by the NAS".into(),
         }
+        .into())
+    }
 
-        // Basic file validation
-        if !file_name.ends_with(".torrent") {
-            debug!("Warning: File name does not end with .torrent extension: {file_name}");
+    /// Picks the backend from the task APIs provided by the NAS
+    fn detect_backend(api_info: &HashMap<String, ApiInfo>) -> Option<Backend> {
+        if api_info.contains_key(TASK_API.name) {
+            Some(DownloadStation2)
+        } else if api_info.contains_key(LEGACY_TASK_API.name) {
+            Some(Legacy)
+        } else {
+            None
         }
+    }
 
-        debug!(
-            "Creating download task from file. Name: {}, Size: {} bytes, Destination: {}",
-            file_name,
-            file_data.len(),
-            destination
-        );
+    /// Looks up the path of the given API and negotiates the highest version
+    /// supported by both the client and the NAS, refreshing a previously cached
+    /// API info once if it doesn't provide the API
+    async fn resolve_api(&self, spec: &ApiSpec) -> Result<ResolvedApi> {
+        let cached = self.api_info.read().await.is_some();
+        let api_info = self.api_info().await?;
+        match Self::negotiate_api(&api_info, spec) {
+            Err(error) if cached => {
+                debug!("{error:#}, refreshing API info");
+                let api_info = self.refresh_api_info().await?;
+                Self::negotiate_api(&api_info, spec)
+            }
+            result => result,
+        }
+    }
 
-        let build_form = |sid: &str| -> Result<(String, multipart::Form)> {
-            let file_part = Part::bytes(file_data.to_vec())
-                .file_name(file_name.to_string())
-                .mime_str("application/x-bittorrent")
-                .context("Failed to create file part")?;
-
-            let form = multipart::Form::new()
-                .text("api", "SYNO.DownloadStation2.Task")
-                .text("version", "2")
-                .text("method", "create")
-                .text("type", "\"file\"")
-                .text("file", "[\"torrent\"]")
-                .text("destination", format!("\"{destination}\""))
-                .text("create_list", "false")
-                .part("torrent", file_part);
-
-            let url = format!("{}{}?_sid={}", self.url, API_PATH, sid);
-            Ok((url, form))
-        };
+    /// Picks the path and version of the given API from the cached API info
+    fn negotiate_api(api_info: &HashMap<String, ApiInfo>, spec: &ApiSpec) -> Result<ResolvedApi> {
+        let info = api_info.get(spec.name).ok_or_else(|| ApiNotAvailable {
+            api: spec.name.into(),
+            message: "API is not provided by the NAS".into(),
+        })?;
+
+        let version = spec.max_version.min(info.max_version);
+        if version < spec.min_version.max(info.min_version) {
+            return Err(ApiNotAvailable {
+                api: spec.name.into(),
+                message: format!(
+                    "no common version, client supports {}-{}, NAS supports {}-{}",
+                    spec.min_version, spec.max_version, info.min_version, info.max_version
+                ),
+            }
+            .into());
+        }
 
-        let send_multipart = |url: String, form: multipart::Form| async move {
-            self.client
-                .post(&url)
-                .multipart(form)
-                .send()
-                .await
-                .context("Failed to send file upload request")?
-                .json::<SynologyResponse<TaskCreated>>()
-                .await
-                .context("Failed to parse file upload response")
-        };
+        debug!("Using {} version {version} at {}", spec.name, info.path);
 
-        // First attempt
-        let sid = self.sid.read().await.clone();
-        let (url, form) = build_form(&sid)?;
-        let response = send_multipart(url, form).await?;
+        Ok(ResolvedApi {
+            path: info.path.clone(),
+            version: version.to_string(),
+        })
+    }
 
-        // Check for session expired
-        let response = if !response.success
-            && response
-                .error
-                .as_ref()
-                .is_some_and(|e| e.code == SESSION_EXPIRED_CODE)
-        {
-            debug!("Session expired during file upload, re-authorizing and retrying");
-            self.authorize().await?;
-            let sid = self.sid.read().await.clone();
-            let (url, form) = build_form(&sid)?;
-            send_multipart(url, form).await?
-        } else {
-            response
-        };
+    /// Gets all Download Station tasks with all additional fields
+    ///
+    /// Use [`SynoDS::list_tasks`] for pagination, sorting and filtering.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_tasks(&self) -> Result<Tasks> {
+        self.list_tasks().send().await
+    }
 
-        // Handle the response
-        if response.success {
-            debug!("Successfully created download task for file: {file_name}");
-            Ok(())
-        } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: "Failed to create task".into(),
-            }
-            .into())
-        } else {
-            Err(InvalidResponse("Failed to create task, unknown error".into()).into())
+    /// Gets all Download Station tasks with only the given additional fields
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_tasks_with(&self, additional: impl Into<AdditionalFields>) -> Result<Tasks> {
+        self.list_tasks().additional(additional).send().await
+    }
+
+    /// Gets detailed information about specific task(s) with all additional fields
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - IDs vector is empty
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_task(&self, ids: Vec<String>) -> Result<TaskInfo> {
+        self.get_task_with(ids, AdditionalFields::ALL).await
+    }
+
+    /// Gets information about specific task(s) with only the given additional fields
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::SynoDS;
+    /// # use syno_download_station::entities::AdditionalField;
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// let info = synods
+    ///     .get_task_with(vec!["dbid_1".into()], AdditionalField::Transfer)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - IDs vector is empty
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_task_with(
+        &self,
+        ids: Vec<String>,
+        additional: impl Into<AdditionalFields>,
+    ) -> Result<TaskInfo> {
+        if ids.is_empty() {
+            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+        }
+
+        let additional = additional.into();
+        let id_string = ids.join(",");
+        if self.backend().await? == Legacy {
+            return self.legacy_get_task(&id_string, additional).await;
         }
+
+        let additional = list::additional_param(additional);
+        let mut all_params = vec![("method", "get"), ("id", id_string.as_str())];
+        if let Some(additional) = &additional {
+            all_params.push(("additional", additional));
+        }
+
+        self.api_call(&TASK_API, &all_params, "get task").await
+    }
+
+    /// Creates a new download task from a URI (HTTP/HTTPS URL or magnet link)
+    ///
+    /// Returns the IDs of the created tasks, see [`SynoDS::create_task_with`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - URI or destination is empty
+    /// - URI doesn't start with http://, https://, or magnet:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    pub async fn create_task(&self, uri: &str, destination: &str) -> Result<TaskCreated> {
+        self.create_task_with(uri, destination, &CreateTaskOptions::default())
+            .await
+    }
+
+    /// Creates a new download task from a torrent file
+    /// Uses multipart/form-data with POST for file uploads
+    ///
+    /// Returns the IDs of the created tasks, see [`SynoDS::create_task_from_file_with`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - File data is empty
+    /// - File name or destination is empty
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Torrent file is invalid or corrupted
+    pub async fn create_task_from_file(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        destination: &str,
+    ) -> Result<TaskCreated> {
+        self.create_task_from_file_with(
+            file_data,
+            file_name,
+            destination,
+            &CreateTaskOptions::default(),
+        )
+        .await
     }
 
     /// Pause a specific task
@@ -446,29 +977,23 @@ impl SynoDS {
     /// - Task cannot be paused (e.g., already paused or in a state that cannot be paused)
     /// - Session is invalid or expired
     pub async fn pause(&self, id: &str) -> Result<()> {
-        let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "pause"));
-            params.push(("id", id));
-            params
-        };
+        if self.backend().await? == Legacy {
+            let params = [("method", "pause"), ("id", id)];
+            let task_operation = self.legacy_task_operation(&params, "pause", id).await?;
+            return match task_operation.failed_task.first() {
+                Some(failed_task) => Err(Api {
+                    api: LEGACY_TASK_API.name.into(),
+                    code: failed_task.error,
+                    message: "Failed to pause task".into(),
+                }
+                .into()),
+                None => Ok(()),
+            };
+        }
 
-        let response = self
-            .make_api_request::<()>(&all_params)
-            .await
-            .context("Failed to pause download task")?;
+        let all_params = [("method", "pause"), ("id", id)];
 
-        if response.success {
-            Ok(())
-        } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: "Failed to pause task".into(),
-            }
-            .into())
-        } else {
-            Err(InvalidResponse("Failed to pause task, unknown error".into()).into())
-        }
+        self.api_call(&TASK_API, &all_params, "pause task").await
     }
 
     /// Resume a specific task
@@ -483,36 +1008,19 @@ impl SynoDS {
     /// - Session is invalid, or expired
     /// - Response data is missing or invalid
     pub async fn resume(&self, id: &str) -> Result<TaskOperation> {
-        let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "resume"));
-            params.push(("id", id));
-            params
-        };
+        if self.backend().await? == Legacy {
+            let params = [("method", "resume"), ("id", id)];
+            return self.legacy_task_operation(&params, "resume", id).await;
+        }
 
-        let response = self
-            .make_api_request::<TaskOperation>(&all_params)
-            .await
-            .context("Failed to resume download task")?;
+        let all_params = [("method", "resume"), ("id", id)];
 
-        if response.success {
-            match response.data {
-                Some(task_operation) => Ok(task_operation),
-                None => Err(InvalidResponse("No data received".into()).into()),
-            }
-        } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: format!("Failed to resume download task id: {}", &id),
-            }
-            .into())
-        } else {
-            Err(InvalidResponse(format!(
-                "Failed to resume download task id: {}, unknown error",
-                &id
-            ))
-            .into())
-        }
+        self.api_call(
+            &TASK_API,
+            &all_params,
+            &format!("resume download task id: {id}"),
+        )
+        .await
     }
 
     /// Complete a specific task
@@ -524,39 +1032,18 @@ impl SynoDS {
     /// - API returns an error response
     /// - Task ID is invalid
     /// - Task cannot be completed (e.g., in a state that cannot be completed)
+    /// - NAS doesn't provide `SYNO.DownloadStation2.Task.Complete` (e.g., with the legacy backend)
     /// - Session is invalid, or expired
     /// - Response data is missing or invalid
     pub async fn complete(&self, id: &str) -> Result<TaskCompleted> {
-        let params = [
-            ("api", "SYNO.DownloadStation2.Task.Complete"),
-            ("version", "1"),
-            ("method", "start"),
-            ("id", id),
-        ];
-
-        let response = self
-            .make_api_request::<TaskCompleted>(&params)
-            .await
-            .context("Failed to complete download task")?;
-
-        if response.success {
-            match response.data {
-                Some(task_completed) => Ok(task_completed),
-                None => Err(InvalidResponse("No data received".into()).into()),
-            }
-        } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: format!("Failed to complete download task id: {}", &id),
-            }
-            .into())
-        } else {
-            Err(InvalidResponse(format!(
-                "Failed to complete download task id: {}, unknown error",
-                &id
-            ))
-            .into())
-        }
+        let params = [("method", "start"), ("id", id)];
+
+        self.api_call(
+            &TASK_COMPLETE_API,
+            &params,
+            &format!("complete download task id: {id}"),
+        )
+        .await
     }
 
     /// Delete a specific task
@@ -571,84 +1058,267 @@ impl SynoDS {
     /// - Session is invalid, or expired
     /// - Response data is missing or invalid
     pub async fn delete_task(&self, id: &str, force_complete: bool) -> Result<TaskOperation> {
+        if self.backend().await? == Legacy {
+            let params = [
+                ("method", "delete"),
+                ("id", id),
+                (
+                    "force_complete",
+                    if force_complete { "true" } else { "false" },
+                ),
+            ];
+            return self.legacy_task_operation(&params, "delete", id).await;
+        }
+
         let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "delete"));
-            params.push(("id", id));
+            let mut params = vec![("method", "delete"), ("id", id)];
             if force_complete {
                 params.push(("force_complete", "true"));
             }
             params
         };
 
+        self.api_call(
+            &TASK_API,
+            &all_params,
+            &format!("delete download task id: {id}"),
+        )
+        .await
+    }
+
+    /// Changes the destination folder of one or more tasks
+    ///
+    /// The destination is a path starting with a shared folder, e.g. `downloads/movies`.
+    /// Not supported by the legacy backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - IDs slice is empty
+    /// - Destination is empty or contains `..` or control characters
+    /// - Destination does not exist ([`SynoError::DestinationNotFound`])
+    /// - Destination is not writable ([`SynoError::DestinationDenied`])
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn edit_task_destination(&self, ids: &[&str], destination: &str) -> Result<()> {
+        if ids.is_empty() {
+            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+        }
+
+        validate_destination(destination)?;
+
+        let id_string = ids.join(",");
+        let destination_param = json_string(destination);
+        let all_params = [
+            ("method", "edit"),
+            ("id", id_string.as_str()),
+            ("destination", &destination_param),
+        ];
+
         let response = self
-            .make_api_request::<TaskOperation>(&all_params)
+            .make_api_request::<()>(&TASK_API, &all_params)
             .await
-            .context("Failed to delete download task")?;
+            .context("Failed to edit download task")?;
 
         if response.success {
-            match response.data {
-                Some(task_operation) => Ok(task_operation),
-                None => Err(InvalidResponse("No data received".into()).into()),
-            }
+            debug!("Moved tasks {id_string} to {destination}");
+            Ok(())
         } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: format!("Failed to delete download task id: {}", &id),
+            Err(match error.code {
+                DESTINATION_NOT_FOUND_CODE => DestinationNotFound {
+                    destination: destination.to_string(),
+                },
+                DESTINATION_DENIED_CODE => DestinationDenied {
+                    destination: destination.to_string(),
+                },
+                code => Api {
+                    api: TASK_API.name.into(),
+                    code,
+                    message: format!("Failed to edit download task id: {id_string}"),
+                },
             }
             .into())
         } else {
             Err(InvalidResponse(format!(
-                "Failed to delete download task id: {}, unknown error",
-                &id
+                "Failed to edit download task id: {id_string}, unknown error"
             ))
             .into())
         }
     }
 
-    /// Clear completed tasks
+    /// Pause several tasks in one request
     ///
     /// # Errors
     ///
     /// Returns an error if:
+    /// - IDs slice is empty
     /// - Network request fails
-    /// - API returns an error response
-    /// - No completed tasks exist
+    /// - API returns an error response that doesn't list the failed tasks
+    /// - Response cannot be parsed
     /// - Session is invalid or expired
-    pub async fn clear_completed(&self) -> Result<()> {
-        let finished_index = (Finished as u8).to_string();
+    pub async fn pause_tasks(&self, ids: &[&str]) -> Result<BatchResult> {
+        if ids.is_empty() {
+            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+        }
+
+        if self.backend().await? == Legacy {
+            return self.legacy_batch_operation("pause", ids, &[]).await;
+        }
+
+        self.batch_operation(&TASK_API, "pause", ids, &[("method", "pause")])
+            .await
+    }
+
+    /// Resume several tasks in one request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - IDs slice is empty
+    /// - Network request fails
+    /// - API returns an error response that doesn't list the failed tasks
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn resume_tasks(&self, ids: &[&str]) -> Result<BatchResult> {
+        if ids.is_empty() {
+            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+        }
+
+        if self.backend().await? == Legacy {
+            return self.legacy_batch_operation("resume", ids, &[]).await;
+        }
+
+        self.batch_operation(&TASK_API, "resume", ids, &[("method", "resume")])
+            .await
+    }
+
+    /// Complete several tasks in one request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - IDs slice is empty
+    /// - Network request fails
+    /// - API returns an error response that doesn't list the failed tasks
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    /// - NAS doesn't provide `SYNO.DownloadStation2.Task.Complete` (e.g., with the legacy backend)
+    pub async fn complete_tasks(&self, ids: &[&str]) -> Result<BatchResult> {
+        if ids.is_empty() {
+            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+        }
+
+        self.batch_operation(&TASK_COMPLETE_API, "complete", ids, &[("method", "start")])
+            .await
+    }
+
+    /// Delete several tasks in one request
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - IDs slice is empty
+    /// - Network request fails
+    /// - API returns an error response that doesn't list the failed tasks
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn delete_tasks(&self, ids: &[&str], force_complete: bool) -> Result<BatchResult> {
+        if ids.is_empty() {
+            return Err(InvalidInput("Task IDs cannot be empty".into()).into());
+        }
+
+        if self.backend().await? == Legacy {
+            let force_complete = if force_complete { "true" } else { "false" };
+            return self
+                .legacy_batch_operation("delete", ids, &[("force_complete", force_complete)])
+                .await;
+        }
+
+        // Same request as `delete_task`, sending `force_complete` only when set
+        let mut params = vec![("method", "delete")];
+        if force_complete {
+            params.push(("force_complete", "true"));
+        }
+        self.batch_operation(&TASK_API, "delete", ids, &params)
+            .await
+    }
+
+    /// Runs an operation on several tasks in one request and collects the per-task results
+    async fn batch_operation(
+        &self,
+        spec: &ApiSpec,
+        action: &str,
+        ids: &[&str],
+        params: &[(&str, &str)],
+    ) -> Result<BatchResult> {
+        let id_string = ids.join(",");
         let all_params = {
-            let mut params = DEFAULT_PARAMS.to_vec();
-            params.push(("method", "delete_condition"));
-            params.push(("status", &finished_index));
-            params
+            let mut all_params = params.to_vec();
+            all_params.push(("id", &id_string));
+            all_params
         };
 
         let response = self
-            .make_api_request::<()>(&all_params)
+            .make_api_request::<TaskOperation>(spec, &all_params)
             .await
-            .context("Failed to clear completed tasks")?;
+            .with_context(|| format!("Failed to {action} download tasks"))?;
 
         if response.success {
-            Ok(())
+            let failed_tasks = response
+                .data
+                .map(|task_operation| task_operation.failed_task)
+                .unwrap_or_default();
+            Ok(BatchResult::new(ids, failed_tasks))
         } else if let Some(error) = response.error {
-            Err(Api {
-                code: error.code,
-                message: "Failed to clear completed tasks".into(),
+            match error.errors {
+                Some(errors) if !errors.failed_task.is_empty() => {
+                    Ok(BatchResult::new(ids, errors.failed_task))
+                }
+                _ => Err(Api {
+                    api: spec.name.into(),
+                    code: error.code,
+                    message: format!("Failed to {action} download task ids: {id_string}"),
+                }
+                .into()),
             }
-            .into())
         } else {
-            Err(InvalidResponse("Failed to clear completed tasks, unknown error".into()).into())
+            Err(InvalidResponse(format!(
+                "Failed to {action} download task ids: {id_string}, unknown error"
+            ))
+            .into())
+        }
+    }
+
+    /// Clear completed tasks
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - No completed tasks exist
+    /// - Session is invalid or expired
+    pub async fn clear_completed(&self) -> Result<()> {
+        if self.backend().await? == Legacy {
+            return self.legacy_clear_completed().await;
         }
+
+        let finished_index = Finished.code().to_string();
+        let all_params = [("method", "delete_condition"), ("status", &finished_index)];
+
+        self.api_call(&TASK_API, &all_params, "clear completed tasks")
+            .await
     }
 
     /// Makes a POST API request with form parameters (no sid, no retry).
-    /// Used by `authorize()` and as the base for `send_with_sid`.
-    async fn make_raw_request<R>(&self, params: &[(&str, &str)]) -> Result<R>
+    /// Used by `authorize()`, `query_api_info()` and as the base for `send_with_sid`.
+    async fn make_raw_request<R>(&self, path: &str, params: &[(&str, &str)]) -> Result<R>
     where
         R: for<'de> serde::Deserialize<'de>,
     {
-        let base_url = format!("{}{}", self.url, API_PATH);
+        let base_url = format!("{}{}/{}", self.url, WEBAPI_PATH, path);
         debug!(
             "Making API request to: {} with {} parameters",
             base_url,
@@ -661,50 +1331,114 @@ impl SynoDS {
             .form(params)
             .send()
             .await
+            .map_err(Network)
             .context("Failed to make API request")?;
 
         debug!("API request status: {}", response.status());
-
-        let status = response.status();
-        if !status.is_success() {
-            return Err(Api {
-                code: i32::from(status.as_u16()),
-                message: format!(
-                    "HTTP request failed with status: {} ({})",
-                    status.as_u16(),
-                    status.canonical_reason().unwrap_or("Unknown")
-                ),
-            }
-            .into());
-        }
+        Self::check_status(response.status())?;
 
         response
             .json::<R>()
             .await
+            .map_err(|error| InvalidResponse(error.to_string()))
             .context("Failed to parse API response".to_string())
     }
 
+    /// Turns an unsuccessful HTTP status into [`SynoError::Http`]
+    fn check_status(status: reqwest::StatusCode) -> Result<()> {
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(Http {
+            status: status.as_u16(),
+            message: status.canonical_reason().unwrap_or("Unknown").into(),
+        }
+        .into())
+    }
+
     /// Clones the sid from the `RwLock`, appends `_sid`, and calls `make_raw_request`.
-    async fn send_with_sid<D>(&self, params: &[(&str, &str)]) -> Result<SynologyResponse<D>>
+    async fn send_with_sid<D>(
+        &self,
+        path: &str,
+        params: &[(&str, &str)],
+    ) -> Result<SynologyResponse<D>>
     where
         D: for<'de> serde::Deserialize<'de>,
     {
-        let sid = self.sid.read().await.clone();
+        let sid = self.sid().await;
         let mut all_params = params.to_vec();
         if !sid.is_empty() {
             all_params.push(("_sid", &sid));
         }
-        self.make_raw_request::<SynologyResponse<D>>(&all_params)
-            .await
+        let response = self
+            .make_raw_request::<SynologyResponse<D>>(path, &all_params)
+            .await?;
+        self.touch_session().await;
+        Ok(response)
+    }
+
+    /// Records the current time as the last use of the session
+    async fn touch_session(&self) {
+        if let Some(active) = self.session.write().await.as_mut() {
+            active.last_used_at = Utc::now();
+        }
     }
 
-    /// Sends an API request with the session ID. On error code 119 (session expired),
-    /// transparently re-authorizes and retries once.
-    async fn make_api_request<D>(&self, params: &[(&str, &str)]) -> Result<SynologyResponse<D>>
+    /// Sends a request to the given API with the negotiated version and the session ID.
+    /// On error code 102 or 104 (API or version not found), refreshes the API info and
+    /// retries once. On error code 119 (session expired), transparently re-authorizes and
+    /// retries once. A missing Download Station API is reported as
+    /// [`SynoError::ServiceNotRunning`] if the package is stopped.
+    async fn make_api_request<D>(
+        &self,
+        spec: &ApiSpec,
+        params: &[(&str, &str)],
+    ) -> Result<SynologyResponse<D>>
     where
         D: for<'de> serde::Deserialize<'de>,
     {
-        let response = self.send_with_sid::<D>(params).await?;
+        let mut api = match self.resolve_api(spec).await {
+            Ok(api) => api,
+            Err(error) => {
+                self.ensure_service_running(spec.name).await?;
+                return Err(error);
+            }
+        };
+        let mut response = self.send_to_api::<D>(spec, &api, params).await?;
+
+        if !response.success
+            && response.error.as_ref().is_some_and(|e| {
+                matches!(
+                    ErrorCode::for_api(spec.name, e.code),
+                    ErrorCode::ApiNotFound | ErrorCode::VersionNotSupported
+                )
+            })
+        {
+            debug!(
+                "{} not found on the NAS, refreshing API info and retrying",
+                spec.name
+            );
+            let api_info = self.refresh_api_info().await?;
+            api = match Self::negotiate_api(&api_info, spec) {
+                Ok(api) => api,
+                Err(error) => {
+                    self.ensure_service_running(spec.name).await?;
+                    return Err(error);
+                }
+            };
+            response = self.send_to_api::<D>(spec, &api, params).await?;
+        }
+
+        if !response.success
+            && response.error.as_ref().is_some_and(|e| {
+                matches!(
+                    ErrorCode::for_api(spec.name, e.code),
+                    ErrorCode::ApiNotFound | ErrorCode::MethodNotFound
+                )
+            })
+        {
+            self.ensure_service_running(spec.name).await?;
+        }
 
         if !response.success
             && response
@@ -716,13 +1450,213 @@ impl SynoDS {
                 "Session expired (error code {SESSION_EXPIRED_CODE}), re-authorizing and retrying"
             );
             self.authorize().await?;
-            return self.send_with_sid::<D>(params).await;
+            return self.send_to_api::<D>(spec, &api, params).await;
+        }
+
+        Ok(response)
+    }
+
+    /// Sends a request with the session ID to a resolved API, adding the `api` and
+    /// `version` parameters
+    async fn send_to_api<D>(
+        &self,
+        spec: &ApiSpec,
+        api: &ResolvedApi,
+        params: &[(&str, &str)],
+    ) -> Result<SynologyResponse<D>>
+    where
+        D: for<'de> serde::Deserialize<'de>,
+    {
+        let mut all_params = vec![("api", spec.name), ("version", api.version.as_str())];
+        all_params.extend_from_slice(params);
+        self.send_with_sid::<D>(&api.path, &all_params).await
+    }
+
+    /// Sends a request with [`Self::make_api_request`] and returns its data, reporting an
+    /// unsuccessful response as [`SynoError::Api`] with the message `Failed to {action}`.
+    /// With `D = ()`, a successful response without data is accepted.
+    async fn api_call<D>(&self, spec: &ApiSpec, params: &[(&str, &str)], action: &str) -> Result<D>
+    where
+        D: for<'de> serde::Deserialize<'de>,
+    {
+        let response = self
+            .make_api_request::<D>(spec, params)
+            .await
+            .with_context(|| format!("Failed to {action}"))?;
+
+        if response.success {
+            match response.data {
+                Some(data) => Ok(data),
+                None => D::deserialize(serde_json::Value::Null)
+                    .map_err(|_| InvalidResponse("No data received".into()).into()),
+            }
+        } else if let Some(error) = response.error {
+            Err(Api {
+                api: spec.name.into(),
+                code: error.code,
+                message: format!("Failed to {action}"),
+            }
+            .into())
+        } else {
+            Err(InvalidResponse(format!("Failed to {action}, unknown error")).into())
+        }
+    }
+
+    /// Sends a multipart/form-data request to the given API with the negotiated version and
+    /// the session ID. `build_form` receives a form with `api` and `version` already set and
+    /// adds the method-specific fields. On error code 119 (session expired), transparently
+    /// re-authorizes and retries once.
+    async fn make_multipart_request<D>(
+        &self,
+        spec: &ApiSpec,
+        build_form: impl Fn(multipart::Form) -> Result<multipart::Form>,
+    ) -> Result<SynologyResponse<D>>
+    where
+        D: for<'de> serde::Deserialize<'de>,
+    {
+        let api = self.resolve_api(spec).await?;
+
+        let build_request = |sid: &str| -> Result<(String, multipart::Form)> {
+            let form = build_form(
+                multipart::Form::new()
+                    .text("api", spec.name)
+                    .text("version", api.version.clone()),
+            )?;
+            let url = format!("{}{}/{}?_sid={}", self.url, WEBAPI_PATH, api.path, sid);
+            Ok((url, form))
+        };
+
+        let send_multipart = |url: String, form: multipart::Form| async move {
+            let response = self
+                .client
+                .post(&url)
+                .multipart(form)
+                .send()
+                .await
+                .map_err(Network)
+                .context("Failed to send file upload request")?;
+
+            debug!("File upload request status: {}", response.status());
+            Self::check_status(response.status())?;
+
+            response
+                .json::<SynologyResponse<D>>()
+                .await
+                .map_err(|error| InvalidResponse(error.to_string()))
+                .context("Failed to parse file upload response")
+        };
+
+        // First attempt
+        let sid = self.sid().await;
+        let (url, form) = build_request(&sid)?;
+        let response = send_multipart(url, form).await?;
+        self.touch_session().await;
+
+        // Check for session expired
+        if !response.success
+            && response
+                .error
+                .as_ref()
+                .is_some_and(|e| e.code == SESSION_EXPIRED_CODE)
+        {
+            debug!("Session expired during file upload, re-authorizing and retrying");
+            self.authorize().await?;
+            let sid = self.sid().await;
+            let (url, form) = build_request(&sid)?;
+            return send_multipart(url, form).await;
         }
 
         Ok(response)
     }
 }
 
+impl Drop for SynoDS {
+    /// Logs out in the background if enabled with [`SynoDSBuilder::logout_on_drop`]
+    /// and the client is still authorized, clearing the session store like
+    /// [`SynoDS::logout`] once the NAS has ended the session. Requires a running Tokio
+    /// runtime. Without a cached `SYNO.API.Info` response, the logout is sent to the
+    /// default `entry.cgi` path with the highest supported `SYNO.API.Auth` version,
+    /// which NAS running DSM 6 or older may reject, keeping the session on the NAS and
+    /// in the store.
+    fn drop(&mut self) {
+        let Some(active) = self.session.get_mut().take() else {
+            return;
+        };
+        if !self.logout_on_drop {
+            return;
+        }
+        let sid = active.session.sid;
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            debug!("No Tokio runtime available, skipping logout on drop");
+            return;
+        };
+        let auth_api = match self.api_info.get_mut() {
+            Some(api_info) => match Self::negotiate_api(api_info, &AUTH_API) {
+                Ok(auth_api) => auth_api,
+                Err(error) => {
+                    warn!("Skipping logout on drop: {error}");
+                    return;
+                }
+            },
+            None => {
+                debug!("API info not queried yet, logging out on drop through {DEFAULT_AUTH_PATH}");
+                ResolvedApi {
+                    path: DEFAULT_AUTH_PATH.into(),
+                    version: AUTH_API.max_version.to_string(),
+                }
+            }
+        };
+        let client = self.client.clone();
+        let session_store = self.session_store.clone();
+        let url = format!("{}{}/{}", self.url, WEBAPI_PATH, auth_api.path);
+        runtime.spawn(async move {
+            let params = [
+                ("api", AUTH_API.name),
+                ("version", &auth_api.version),
+                ("method", "logout"),
+                ("_sid", &sid),
+            ];
+            let response = match client.post(&url).form(&params).send().await {
+                Ok(response) => response.json::<SynologyResponse<()>>().await,
+                Err(error) => Err(error),
+            };
+            let ended = match response {
+                Ok(response) if response.success => true,
+                Ok(SynologyResponse {
+                    error: Some(error), ..
+                }) if session_ended(error.code) => true,
+                Ok(SynologyResponse {
+                    error: Some(error), ..
+                }) => {
+                    warn!(
+                        "Failed to log out on drop (error code {}): {}",
+                        error.code,
+                        ErrorCode::auth(error.code)
+                    );
+                    false
+                }
+                Ok(_) => {
+                    warn!("Failed to log out on drop, unknown error");
+                    false
+                }
+                Err(error) => {
+                    debug!("Failed to log out on drop: {error}");
+                    false
+                }
+            };
+            if !ended {
+                return;
+            }
+            if let Some(session_store) = session_store {
+                if let Err(error) = session_store.clear() {
+                    warn!("Failed to clear stored session: {error:#}");
+                }
+            }
+        });
+    }
+}
+
 /// Builder for [`SynoDS`] client
 #[derive(Default)]
 pub struct SynoDSBuilder {
@@ -730,6 +1664,12 @@ pub struct SynoDSBuilder {
     username: Option<String>,
     password: Option<String>,
     timeout: Option<u64>,
+    backend: Option<Backend>,
+    device_name: Option<String>,
+    device_id: Option<String>,
+    logout_on_drop: bool,
+    session: Option<Session>,
+    session_store: Option<Arc<dyn SessionStore>>,
 }
 
 impl SynoDSBuilder {
@@ -761,6 +1701,58 @@ impl SynoDSBuilder {
         self
     }
 
+    /// Forces the backend used to manage tasks instead of detecting it from the API info
+    #[must_use]
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Sets the name the client registers under when a trusted device is requested
+    /// with [`SynoDS::authorize_with_otp`]
+    #[must_use]
+    pub fn device_name(mut self, device_name: impl Into<String>) -> Self {
+        self.device_name = Some(device_name.into());
+        self
+    }
+
+    /// Sets the device ID of a trusted device, allowing login without an OTP code
+    #[must_use]
+    pub fn device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+
+    /// Logs out automatically when the client is dropped
+    ///
+    /// The logout request is spawned on the current Tokio runtime and not awaited,
+    /// use [`SynoDS::close`] to log out and wait for the result.
+    #[must_use]
+    pub fn logout_on_drop(mut self, logout_on_drop: bool) -> Self {
+        self.logout_on_drop = logout_on_drop;
+        self
+    }
+
+    /// Restores a previously exported session instead of logging in
+    ///
+    /// The session is not validated upfront. If the NAS rejects it as expired,
+    /// the client re-authorizes transparently with the configured credentials.
+    #[must_use]
+    pub fn session(mut self, session: Session) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Sets the store used to restore the session on build, save it after every login
+    /// and clear it on logout
+    ///
+    /// A session set with [`SynoDSBuilder::session`] takes precedence over the stored one.
+    #[must_use]
+    pub fn session_store(mut self, session_store: impl SessionStore + 'static) -> Self {
+        self.session_store = Some(Arc::new(session_store));
+        self
+    }
+
     /// Builds the [`SynoDS`] client
     ///
     /// # Errors
@@ -769,6 +1761,7 @@ impl SynoDSBuilder {
     /// - Required fields (url, username, password) are not provided
     /// - Host URL doesn't start with "http://" or "https://"
     /// - Any field contains invalid data
+    /// - Stored session cannot be loaded from the session store
     pub fn build(self) -> Result<SynoDS> {
         let url = self
             .url
@@ -782,7 +1775,31 @@ impl SynoDSBuilder {
 
         let timeout = self.timeout.unwrap_or(3000);
 
-        let client = SynoDS::new(url, username, password, timeout)?;
+        let mut client = SynoDS::new(url, username, password, timeout)?;
+        client.backend = self.backend;
+        if let Some(device_name) = self.device_name {
+            client.device_name = device_name;
+        }
+        client.device_id = RwLock::new(self.device_id);
+        client.logout_on_drop = self.logout_on_drop;
+
+        let session = match (self.session, &self.session_store) {
+            (Some(session), _) => Some(session),
+            (None, Some(session_store)) => session_store
+                .load()
+                .context("Failed to load stored session")?,
+            (None, None) => None,
+        };
+        if let Some(session) = session {
+            if client.device_id.get_mut().is_none() {
+                client.device_id.get_mut().clone_from(&session.device_id);
+            }
+            *client.session.get_mut() = Some(ActiveSession {
+                last_used_at: session.obtained_at,
+                session,
+            });
+        }
+        client.session_store = self.session_store;
 
         Ok(client)
     }
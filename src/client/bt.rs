@@ -0,0 +1,451 @@
+use crate::client::SynoError::InvalidInput;
+use crate::client::{ApiSpec, SynoDS, json_index_list, json_string};
+use crate::entities::{AdditionalField, FilePriority, TaskLimits};
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::ops::Range;
+use url::{Host, Url};
+
+const BT_FILE_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.BT.File",
+    min_version: 1,
+    max_version: 2,
+};
+
+const BT_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.BT",
+    min_version: 1,
+    max_version: 2,
+};
+
+const BT_TRACKER_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.BT.Tracker",
+    min_version: 1,
+    max_version: 2,
+};
+
+/// Changes to the limits of a task, applied with [`SynoDS::set_task_limits`]
+///
+/// Only the limits that are set are changed, use 0 to remove a limit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskLimitsUpdate {
+    max_download_rate: Option<u32>,
+    max_upload_rate: Option<u32>,
+    seeding_ratio: Option<f64>,
+    seeding_interval: Option<u32>,
+}
+
+impl TaskLimitsUpdate {
+    /// Creates an update that doesn't change any limit
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum download rate in KB/s
+    #[must_use]
+    pub fn max_download_rate(mut self, rate: u32) -> Self {
+        self.max_download_rate = Some(rate);
+        self
+    }
+
+    /// Sets the maximum upload rate in KB/s
+    #[must_use]
+    pub fn max_upload_rate(mut self, rate: u32) -> Self {
+        self.max_upload_rate = Some(rate);
+        self
+    }
+
+    /// Sets the share ratio after which seeding stops
+    #[must_use]
+    pub fn seeding_ratio(mut self, ratio: f64) -> Self {
+        self.seeding_ratio = Some(ratio);
+        self
+    }
+
+    /// Sets the number of minutes after which seeding stops
+    #[must_use]
+    pub fn seeding_interval(mut self, minutes: u32) -> Self {
+        self.seeding_interval = Some(minutes);
+        self
+    }
+
+    /// Whether the update doesn't change any limit
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Result of replacing the trackers of one task with [`SynoDS::replace_tracker_host`]
+#[derive(Debug)]
+pub struct TrackerReplacement {
+    /// ID of the task
+    pub task_id: String,
+    /// Announce URLs that were replaced
+    pub old_urls: Vec<String>,
+    /// Announce URLs that replaced them
+    pub new_urls: Vec<String>,
+    /// Why the trackers of the task could not be replaced, `None` on success
+    pub error: Option<anyhow::Error>,
+}
+
+impl TrackerReplacement {
+    /// Whether the trackers were replaced
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Returns the URL with the host replaced if its host is `old_host`
+///
+/// Only the host is swapped in the original string, the rest of the URL is kept as is
+/// instead of being normalized, as trackers may compare announce URLs byte for byte.
+fn replace_host(url: &str, old_host: &str, new_host: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let host = parsed
+        .host_str()
+        .filter(|host| host.eq_ignore_ascii_case(old_host))?;
+
+    // Skip hosts written differently than they are parsed, e.g. percent-encoded
+    let range = host_range(url).filter(|range| url[range.clone()].eq_ignore_ascii_case(host))?;
+    let new_host = Host::parse(new_host).ok()?.to_string();
+
+    let mut replaced = url.to_string();
+    replaced.replace_range(range, &new_host);
+    Some(replaced)
+}
+
+/// Byte range of the host in a URL with an authority, e.g. `scheme://user@host:port/path`
+fn host_range(url: &str) -> Option<Range<usize>> {
+    let authority_start = url.find("://")? + 3;
+    let authority_end = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |end| authority_start + end);
+    let authority = &url[authority_start..authority_end];
+
+    let host_start = authority.rfind('@').map_or(0, |at| at + 1);
+    let host_and_port = &authority[host_start..];
+    let host_end = if host_and_port.starts_with('[') {
+        host_and_port.find(']')? + 1
+    } else {
+        host_and_port.rfind(':').unwrap_or(host_and_port.len())
+    };
+
+    let start = authority_start + host_start;
+    Some(start..start + host_end)
+}
+
+impl SynoDS {
+    /// Marks files of a `BitTorrent` task as wanted or unwanted, unwanted files are
+    /// not downloaded
+    ///
+    /// File indexes are the [`crate::entities::File::index`] values of the task.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Task ID is empty or no file indexes are given
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task.BT.File` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn set_files_wanted(
+        &self,
+        task_id: &str,
+        file_indexes: &[u32],
+        wanted: bool,
+    ) -> Result<()> {
+        self.edit_files(
+            task_id,
+            file_indexes,
+            ("wanted", if wanted { "true" } else { "false" }),
+        )
+        .await
+    }
+
+    /// Sets the download priority of files of a `BitTorrent` task
+    ///
+    /// File indexes are the [`crate::entities::File::index`] values of the task.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Task ID is empty or no file indexes are given
+    /// - Priority is [`FilePriority::Unknown`]
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task.BT.File` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn set_files_priority(
+        &self,
+        task_id: &str,
+        file_indexes: &[u32],
+        priority: FilePriority,
+    ) -> Result<()> {
+        if priority == FilePriority::Unknown {
+            return Err(InvalidInput("Unknown file priority cannot be set".into()).into());
+        }
+
+        let priority = json_string(priority.as_str());
+        self.edit_files(task_id, file_indexes, ("priority", &priority))
+            .await
+    }
+
+    /// Runs the `set` method of `SYNO.DownloadStation2.Task.BT.File` with one setting
+    async fn edit_files(
+        &self,
+        task_id: &str,
+        file_indexes: &[u32],
+        setting: (&str, &str),
+    ) -> Result<()> {
+        if task_id.is_empty() {
+            return Err(InvalidInput("Task ID cannot be empty".into()).into());
+        }
+
+        if file_indexes.is_empty() {
+            return Err(InvalidInput("File indexes cannot be empty".into()).into());
+        }
+
+        debug!(
+            "Setting {}={} for files {file_indexes:?} of task {task_id}",
+            setting.0, setting.1
+        );
+
+        let task_id = json_string(task_id);
+        let index = json_index_list(file_indexes);
+        let all_params = [
+            ("method", "set"),
+            ("task_id", task_id.as_str()),
+            ("index", &index),
+            setting,
+        ];
+
+        self.api_call(&BT_FILE_API, &all_params, "edit task files")
+            .await
+    }
+
+    /// Adds trackers to a `BitTorrent` task
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Task ID is empty or no tracker URLs are given
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task.BT.Tracker` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn add_trackers(&self, task_id: &str, urls: &[&str]) -> Result<()> {
+        self.edit_trackers("add", task_id, urls).await
+    }
+
+    /// Removes trackers from a `BitTorrent` task
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Task ID is empty or no tracker URLs are given
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task.BT.Tracker` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn remove_trackers(&self, task_id: &str, urls: &[&str]) -> Result<()> {
+        self.edit_trackers("delete", task_id, urls).await
+    }
+
+    /// Replaces the host of announce URLs across all tasks, for example after a private
+    /// tracker moved to a new domain
+    ///
+    /// For every task with a tracker on `old_host`, the rewritten URLs are added before the
+    /// old ones are removed, so the task keeps a working tracker if the removal fails.
+    /// Tasks without such a tracker are not included in the result.
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::SynoDS;
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// for replacement in synods
+    ///     .replace_tracker_host("tracker.example.com", "tracker.example.org")
+    ///     .await?
+    /// {
+    ///     if let Some(error) = &replacement.error {
+    ///         eprintln!("{}: {error:#}", replacement.task_id);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A host is empty or the new host is not a valid host name
+    /// - The task list cannot be fetched
+    ///
+    /// Errors while editing a single task are reported in its [`TrackerReplacement`].
+    pub async fn replace_tracker_host(
+        &self,
+        old_host: &str,
+        new_host: &str,
+    ) -> Result<Vec<TrackerReplacement>> {
+        if old_host.is_empty() || new_host.is_empty() {
+            return Err(InvalidInput("Tracker hosts cannot be empty".into()).into());
+        }
+        // The new host is spliced into the URLs, so anything else such as a port is rejected
+        if Host::parse(new_host).is_err() {
+            return Err(InvalidInput(format!("Invalid tracker host: {new_host}")).into());
+        }
+
+        let tasks = self
+            .get_tasks_with(AdditionalField::Tracker)
+            .await
+            .context("Failed to replace tracker host")?;
+
+        let mut replacements = Vec::new();
+        for task in tasks.task {
+            let trackers = task
+                .additional
+                .and_then(|additional| additional.tracker)
+                .unwrap_or_default();
+
+            let (old_urls, new_urls): (Vec<_>, Vec<_>) = trackers
+                .into_iter()
+                .filter_map(|tracker| {
+                    replace_host(&tracker.url, old_host, new_host).map(|new| (tracker.url, new))
+                })
+                .unzip();
+            if old_urls.is_empty() {
+                continue;
+            }
+
+            let added = new_urls.iter().map(String::as_str).collect::<Vec<_>>();
+            let removed = old_urls.iter().map(String::as_str).collect::<Vec<_>>();
+            let result = match self.add_trackers(&task.id, &added).await {
+                Ok(()) => self.remove_trackers(&task.id, &removed).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = &result {
+                warn!("Failed to replace trackers of task {}: {error:#}", task.id);
+            }
+
+            replacements.push(TrackerReplacement {
+                task_id: task.id,
+                old_urls,
+                new_urls,
+                error: result.err(),
+            });
+        }
+
+        Ok(replacements)
+    }
+
+    /// Gets the bandwidth and seeding limits of a `BitTorrent` task
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Task ID is empty
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task.BT` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_task_limits(&self, task_id: &str) -> Result<TaskLimits> {
+        if task_id.is_empty() {
+            return Err(InvalidInput("Task ID cannot be empty".into()).into());
+        }
+
+        let task_id = json_string(task_id);
+        let all_params = [("method", "get"), ("task_id", task_id.as_str())];
+
+        self.api_call(&BT_API, &all_params, "get task limits").await
+    }
+
+    /// Changes the bandwidth and seeding limits of a `BitTorrent` task
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::{SynoDS, TaskLimitsUpdate};
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// let update = TaskLimitsUpdate::new()
+    ///     .max_upload_rate(512)
+    ///     .seeding_ratio(2.0);
+    /// synods.set_task_limits("dbid_1", &update).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Task ID is empty
+    /// - The update doesn't change any limit
+    /// - Seeding ratio is negative or not finite
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Task.BT` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn set_task_limits(&self, task_id: &str, update: &TaskLimitsUpdate) -> Result<()> {
+        if task_id.is_empty() {
+            return Err(InvalidInput("Task ID cannot be empty".into()).into());
+        }
+
+        if update.is_empty() {
+            return Err(InvalidInput("Task limits update cannot be empty".into()).into());
+        }
+
+        if let Some(ratio) = update.seeding_ratio {
+            if !ratio.is_finite() || ratio < 0.0 {
+                return Err(InvalidInput(format!(
+                    "Seeding ratio must be a non-negative number, got: {ratio}"
+                ))
+                .into());
+            }
+        }
+
+        debug!("Setting limits of task {task_id}: {update:?}");
+
+        let task_id = json_string(task_id);
+        let max_download_rate = update.max_download_rate.map(|rate| rate.to_string());
+        let max_upload_rate = update.max_upload_rate.map(|rate| rate.to_string());
+        let seeding_ratio = update.seeding_ratio.map(|ratio| ratio.to_string());
+        let seeding_interval = update.seeding_interval.map(|minutes| minutes.to_string());
+
+        let mut all_params = vec![("method", "set"), ("task_id", task_id.as_str())];
+        for (name, value) in [
+            ("max_download_rate", &max_download_rate),
+            ("max_upload_rate", &max_upload_rate),
+            ("seeding_ratio", &seeding_ratio),
+            ("seeding_interval", &seeding_interval),
+        ] {
+            if let Some(value) = value {
+                all_params.push((name, value));
+            }
+        }
+
+        self.api_call(&BT_API, &all_params, "set task limits").await
+    }
+
+    /// Runs the `add` or `delete` method of `SYNO.DownloadStation2.Task.BT.Tracker`
+    async fn edit_trackers(&self, method: &str, task_id: &str, urls: &[&str]) -> Result<()> {
+        if task_id.is_empty() {
+            return Err(InvalidInput("Task ID cannot be empty".into()).into());
+        }
+
+        if urls.is_empty() || urls.iter().any(|url| url.is_empty()) {
+            return Err(InvalidInput("Tracker URLs cannot be empty".into()).into());
+        }
+
+        debug!("Running tracker {method} for task {task_id}: {urls:?}");
+
+        let task_id = json_string(task_id);
+        let trackers = serde_json::to_string(urls).context("Failed to serialize trackers")?;
+        let all_params = [
+            ("method", method),
+            ("task_id", task_id.as_str()),
+            ("tracker", &trackers),
+        ];
+
+        self.api_call(&BT_TRACKER_API, &all_params, "edit task trackers")
+            .await
+    }
+}
@@ -0,0 +1,367 @@
+use crate::client::Backend::Legacy;
+use crate::client::SynoError::{Api, InvalidInput, InvalidResponse};
+use crate::client::{ApiSpec, SynoDS, TASK_API, json_index_list, json_string};
+use crate::entities::{TaskCreated, TaskList};
+use anyhow::{Context, Result};
+use log::debug;
+use reqwest::multipart::Part;
+
+const TASK_LIST_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.List",
+    min_version: 1,
+    max_version: 2,
+};
+
+const TASK_LIST_POLLING_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.List.Polling",
+    min_version: 1,
+    max_version: 2,
+};
+
+/// Options for creating a task with [`SynoDS::create_task_with`] or
+/// [`SynoDS::create_task_from_file_with`]
+#[derive(Debug, Clone)]
+pub struct CreateTaskOptions {
+    extract_password: Option<String>,
+    create_list: bool,
+    create_subfolder: bool,
+}
+
+impl Default for CreateTaskOptions {
+    fn default() -> Self {
+        Self {
+            extract_password: None,
+            create_list: false,
+            create_subfolder: true,
+        }
+    }
+}
+
+impl CreateTaskOptions {
+    /// Creates options for a task created right away, without a password
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the password used to extract downloaded archives
+    #[must_use]
+    pub fn extract_password(mut self, password: impl Into<String>) -> Self {
+        self.extract_password = Some(password.into());
+        self
+    }
+
+    /// Creates a file list instead of a task, so the wanted files can be chosen with
+    /// [`SynoDS::get_task_list`] and [`SynoDS::confirm_task_list`].
+    /// Not supported by the legacy backend.
+    #[must_use]
+    pub fn create_list(mut self, create_list: bool) -> Self {
+        self.create_list = create_list;
+        self
+    }
+
+    /// Whether [`SynoDS::confirm_task_list`] puts the chosen files in a subfolder named
+    /// after the task, true by default
+    #[must_use]
+    pub fn create_subfolder(mut self, create_subfolder: bool) -> Self {
+        self.create_subfolder = create_subfolder;
+        self
+    }
+}
+
+impl SynoDS {
+    /// Creates a new download task from a URI (HTTP/HTTPS URL or magnet link) with the
+    /// given options
+    ///
+    /// The legacy backend doesn't report the IDs of created tasks, so the returned
+    /// [`TaskCreated`] is empty there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - URI or destination is empty
+    /// - URI doesn't start with http://, https://, or magnet:
+    /// - A file list is requested from the legacy backend
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    pub async fn create_task_with(
+        &self,
+        uri: &str,
+        destination: &str,
+        options: &CreateTaskOptions,
+    ) -> Result<TaskCreated> {
+        // Validate input parameters
+        if uri.is_empty() {
+            return Err(InvalidInput("URI cannot be empty".into()).into());
+        }
+
+        if destination.is_empty() {
+            return Err(InvalidInput("Destination path cannot be empty".into()).into());
+        }
+
+        // Basic URI validation
+        if !uri.starts_with("http://")
+            && !uri.starts_with("https://")
+            && !uri.starts_with("magnet:")
+        {
+            return Err(InvalidInput(format!(
+                "URI must start with http://, https://, or magnet:, got: {uri}"
+            ))
+            .into());
+        }
+
+        debug!("Creating download task. URI: {uri}, Destination: {destination}");
+
+        if self.backend().await? == Legacy {
+            if options.create_list {
+                return Err(InvalidInput(
+                    "File lists are not supported by the legacy backend".into(),
+                )
+                .into());
+            }
+            return self
+                .legacy_create_task(uri, destination, options.extract_password.as_deref())
+                .await;
+        }
+
+        let extract_password = options.extract_password.as_deref().map(json_string);
+
+        // Parameters for the create task API call
+        let mut all_params = vec![
+            ("method", "create"),
+            ("type", "\"url\""),
+            ("destination", destination),
+            ("url", uri),
+            (
+                "create_list",
+                if options.create_list { "true" } else { "false" },
+            ),
+        ];
+        if let Some(extract_password) = &extract_password {
+            all_params.push(("extract_password", extract_password));
+        }
+
+        // The response may come without data
+        let created: Option<TaskCreated> =
+            self.api_call(&TASK_API, &all_params, "create task").await?;
+        debug!("Successfully created download task for URI: {uri}");
+        Ok(created.unwrap_or_default())
+    }
+
+    /// Creates a new download task from a torrent file with the given options
+    /// Uses multipart/form-data with POST for file uploads
+    ///
+    /// The legacy backend doesn't report the IDs of created tasks, so the returned
+    /// [`TaskCreated`] is empty there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - File data is empty
+    /// - File name or destination is empty
+    /// - A file list is requested from the legacy backend
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Torrent file is invalid or corrupted
+    pub async fn create_task_from_file_with(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        destination: &str,
+        options: &CreateTaskOptions,
+    ) -> Result<TaskCreated> {
+        // Validate input parameters
+        if file_data.is_empty() {
+            return Err(InvalidInput("File data cannot be empty".into()).into());
+        }
+
+        if file_name.is_empty() {
+            return Err(InvalidInput("File name cannot be empty".into()).into());
+        }
+
+        if destination.is_empty() {
+            return Err(InvalidInput("Destination path cannot be empty".into()).into());
+        }
+
+        // Basic file validation
+        if !file_name.ends_with(".torrent") {
+            debug!("Warning: File name does not end with .torrent extension: {file_name}");
+        }
+
+        debug!(
+            "Creating download task from file. Name: {}, Size: {} bytes, Destination: {}",
+            file_name,
+            file_data.len(),
+            destination
+        );
+
+        if self.backend().await? == Legacy {
+            if options.create_list {
+                return Err(InvalidInput(
+                    "File lists are not supported by the legacy backend".into(),
+                )
+                .into());
+            }
+            return self
+                .legacy_create_task_from_file(
+                    file_data,
+                    file_name,
+                    destination,
+                    options.extract_password.as_deref(),
+                )
+                .await;
+        }
+
+        let response = self
+            .make_multipart_request::<TaskCreated>(&TASK_API, |form| {
+                let file_part = Part::bytes(file_data.to_vec())
+                    .file_name(file_name.to_string())
+                    .mime_str("application/x-bittorrent")
+                    .context("Failed to create file part")?;
+
+                let mut form = form
+                    .text("method", "create")
+                    .text("type", "\"file\"")
+                    .text("file", "[\"torrent\"]")
+                    .text("destination", json_string(destination))
+                    .text("create_list", options.create_list.to_string());
+                if let Some(extract_password) = &options.extract_password {
+                    form = form.text("extract_password", json_string(extract_password));
+                }
+
+                Ok(form.part("torrent", file_part))
+            })
+            .await?;
+
+        // Handle the response
+        if response.success {
+            debug!("Successfully created download task for file: {file_name}");
+            Ok(response.data.unwrap_or_default())
+        } else if let Some(error) = response.error {
+            Err(Api {
+                api: TASK_API.name.into(),
+                code: error.code,
+                message: "Failed to create task".into(),
+            }
+            .into())
+        } else {
+            Err(InvalidResponse("Failed to create task, unknown error".into()).into())
+        }
+    }
+
+    /// Gets the files of a task created with [`CreateTaskOptions::create_list`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - List ID is empty
+    /// - The NAS doesn't provide file lists (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_task_list(&self, list_id: &str) -> Result<TaskList> {
+        if list_id.is_empty() {
+            return Err(InvalidInput("List ID cannot be empty".into()).into());
+        }
+
+        let list_id = json_string(list_id);
+        let all_params = [("method", "get"), ("list_id", list_id.as_str())];
+
+        self.api_call(&TASK_LIST_API, &all_params, "get task list")
+            .await
+    }
+
+    /// Confirms a file list, creating a task that downloads the files with the given
+    /// indexes (see [`crate::entities::TaskListFile::index`]) to the destination
+    ///
+    /// The extract password and subfolder of the options are used, `create_list` is ignored.
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::{CreateTaskOptions, SynoDS};
+    /// # async fn example(synods: &SynoDS, magnet: &str) -> anyhow::Result<()> {
+    /// let created = synods
+    ///     .create_task_with(magnet, "downloads", &CreateTaskOptions::new().create_list(true))
+    ///     .await?;
+    /// let list_id = &created.list_id[0];
+    /// let list = synods.get_task_list(list_id).await?;
+    /// let wanted = list
+    ///     .files
+    ///     .iter()
+    ///     .filter(|file| file.name.ends_with(".mkv"))
+    ///     .map(|file| file.index)
+    ///     .collect::<Vec<_>>();
+    /// let created = synods
+    ///     .confirm_task_list(list_id, &wanted, "downloads", &CreateTaskOptions::new())
+    ///     .await?;
+    /// println!("Created tasks {:?}", created.task_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - List ID or destination is empty
+    /// - No files are selected
+    /// - The NAS doesn't provide file lists (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn confirm_task_list(
+        &self,
+        list_id: &str,
+        file_indexes: &[u32],
+        destination: &str,
+        options: &CreateTaskOptions,
+    ) -> Result<TaskCreated> {
+        if list_id.is_empty() {
+            return Err(InvalidInput("List ID cannot be empty".into()).into());
+        }
+
+        if file_indexes.is_empty() {
+            return Err(InvalidInput("At least one file must be selected".into()).into());
+        }
+
+        if destination.is_empty() {
+            return Err(InvalidInput("Destination path cannot be empty".into()).into());
+        }
+
+        let list_id_param = json_string(list_id);
+        let file_indexes = json_index_list(file_indexes);
+        let destination = json_string(destination);
+        let extract_password = options.extract_password.as_deref().map(json_string);
+
+        let mut all_params = vec![
+            ("method", "download"),
+            ("list_id", list_id_param.as_str()),
+            ("file_indexes", &file_indexes),
+            ("destination", &destination),
+            (
+                "create_subfolder",
+                if options.create_subfolder {
+                    "true"
+                } else {
+                    "false"
+                },
+            ),
+        ];
+        if let Some(extract_password) = &extract_password {
+            all_params.push(("extract_password", extract_password));
+        }
+
+        // The response may come without data
+        let created: Option<TaskCreated> = self
+            .api_call(&TASK_LIST_POLLING_API, &all_params, "confirm task list")
+            .await?;
+        debug!("Successfully confirmed task list {list_id}");
+        let mut created = created.unwrap_or_default();
+        if created.list_id.is_empty() {
+            created.list_id.push(list_id.to_string());
+        }
+        Ok(created)
+    }
+}
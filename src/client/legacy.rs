@@ -0,0 +1,520 @@
+//! Backend for the legacy `SYNO.DownloadStation.Task` API, used on DSM versions
+//! that don't provide the `SYNO.DownloadStation2` family of APIs.
+//!
+//! Responses are mapped onto the same entities as the `DownloadStation2` backend,
+//! so callers don't need to know which backend the NAS supports.
+
+use crate::client::SynoError::{Api, InvalidResponse};
+use crate::client::{ApiSpec, SynoDS};
+use crate::entities::{
+    AdditionalField, AdditionalFields, AdditionalTaskInfo, BatchResult, Detail, FailedTask, File,
+    FilePriority, Peer, StatusExtra, Task, TaskCreated, TaskInfo, TaskOperation, TaskStatus, Tasks,
+    Tracker, Transfer, TransferStatistics,
+};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::debug;
+use reqwest::multipart::Part;
+use serde::Deserialize;
+
+pub(super) const LEGACY_TASK_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation.Task",
+    min_version: 1,
+    max_version: 3,
+};
+
+const LEGACY_STATISTIC_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation.Statistic",
+    min_version: 1,
+    max_version: 1,
+};
+
+/// Collection of tasks returned by the legacy `list` method
+#[derive(Deserialize, Debug)]
+struct LegacyTasks {
+    #[serde(default)]
+    offset: u32,
+    tasks: Vec<LegacyTask>,
+    total: u32,
+}
+
+/// Tasks returned by the legacy `getinfo` method
+#[derive(Deserialize, Debug)]
+struct LegacyTaskInfo {
+    tasks: Vec<LegacyTask>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyTask {
+    id: String,
+    #[serde(default)]
+    username: String,
+    #[serde(rename = "type")]
+    task_type: String,
+    title: String,
+    size: u64,
+    status: LegacyStatus,
+    status_extra: Option<LegacyStatusExtra>,
+    additional: Option<LegacyAdditional>,
+}
+
+/// Status number standing in for legacy statuses unknown to the client, as the legacy
+/// API reports statuses by name only, see [`TaskStatus::Unknown`]
+const UNKNOWN_STATUS_CODE: u32 = 0;
+
+/// Task status as reported by the legacy API
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum LegacyStatus {
+    Waiting,
+    Downloading,
+    Paused,
+    Finishing,
+    Finished,
+    HashChecking,
+    Seeding,
+    FilehostingWaiting,
+    Extracting,
+    Error,
+    /// Status name not known to this client
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Deserialize, Default, Debug)]
+struct LegacyStatusExtra {
+    error_detail: Option<String>,
+    unzip_progress: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyAdditional {
+    detail: Option<LegacyDetail>,
+    file: Option<Vec<LegacyFile>>,
+    peer: Option<Vec<Peer>>,
+    tracker: Option<Vec<Tracker>>,
+    transfer: Option<LegacyTransfer>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct LegacyDetail {
+    completed_time: i64,
+    connected_leechers: u32,
+    connected_peers: u32,
+    connected_seeders: u32,
+    create_time: i64,
+    destination: String,
+    seedelapsed: u64,
+    started_time: i64,
+    total_peers: u32,
+    total_pieces: u32,
+    unzip_password: Option<String>,
+    uri: String,
+    waiting_seconds: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyFile {
+    filename: String,
+    priority: FilePriority,
+    size: u64,
+    size_downloaded: u64,
+    #[serde(default = "default_wanted")]
+    wanted: bool,
+}
+
+fn default_wanted() -> bool {
+    true
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyTransfer {
+    #[serde(default)]
+    downloaded_pieces: u32,
+    size_downloaded: u64,
+    size_uploaded: u64,
+    speed_download: u64,
+    speed_upload: u64,
+}
+
+/// Per-task result of the legacy `pause`, `resume` and `delete` methods
+#[derive(Deserialize, Debug)]
+struct LegacyOperationResult {
+    error: i32,
+    id: String,
+}
+
+impl LegacyStatus {
+    /// Maps the legacy status onto [`TaskStatus`], using the error detail to pick
+    /// the specific error variant
+    fn to_task_status(&self, error_detail: Option<&str>) -> TaskStatus {
+        match self {
+            LegacyStatus::Waiting => TaskStatus::Waiting,
+            LegacyStatus::Downloading => TaskStatus::Downloading,
+            LegacyStatus::Paused => TaskStatus::Paused,
+            LegacyStatus::Finishing => TaskStatus::Finishing,
+            LegacyStatus::Finished => TaskStatus::Finished,
+            LegacyStatus::HashChecking => TaskStatus::HashChecking,
+            LegacyStatus::Seeding => TaskStatus::Seeding,
+            LegacyStatus::FilehostingWaiting => TaskStatus::FilehostingWaiting,
+            LegacyStatus::Extracting => TaskStatus::Extracting,
+            LegacyStatus::Error => error_status(error_detail),
+            LegacyStatus::Unknown(_) => TaskStatus::Unknown(UNKNOWN_STATUS_CODE),
+        }
+    }
+}
+
+/// Maps the legacy `status_extra.error_detail` string onto an error [`TaskStatus`]
+fn error_status(error_detail: Option<&str>) -> TaskStatus {
+    match error_detail.unwrap_or_default() {
+        "broken_link" => TaskStatus::ErrorBrokenLink,
+        "destination_not_exist" => TaskStatus::ErrorDestNoExist,
+        "destination_denied" => TaskStatus::ErrorDestDeny,
+        "disk_full" => TaskStatus::ErrorDiskFull,
+        "quota_reached" => TaskStatus::ErrorQuotaReached,
+        "timeout" => TaskStatus::ErrorTimeout,
+        "exceed_max_file_system_size" => TaskStatus::ErrorExceedMaxFsSize,
+        "exceed_max_temp_file_system_size" => TaskStatus::ErrorExceedMaxTempFsSize,
+        "exceed_max_destination_file_system_size" => TaskStatus::ErrorExceedMaxDestFsSize,
+        "name_too_long_encryption" => TaskStatus::ErrorNameTooLongEncryption,
+        "name_too_long" => TaskStatus::ErrorNameTooLong,
+        "torrent_duplicate" => TaskStatus::ErrorTorrentDuplicate,
+        "file_not_exist" => TaskStatus::ErrorFileNoExist,
+        "required_premium_account" => TaskStatus::ErrorRequiredPremium,
+        "not_supported_type" => TaskStatus::ErrorNotSupportType,
+        "ftp_encryption_not_supported_type" => TaskStatus::ErrorFtpEncryptionNotSupportType,
+        "extract_failed" => TaskStatus::ErrorExtractFail,
+        "extract_failed_wrong_password" => TaskStatus::ErrorExtractWrongPassword,
+        "extract_failed_invalid_archive" => TaskStatus::ErrorExtractInvalidArchive,
+        "extract_failed_quota_reached" => TaskStatus::ErrorExtractQuotaReached,
+        "extract_failed_disk_full" => TaskStatus::ErrorExtractDiskFull,
+        "torrent_invalid" => TaskStatus::ErrorTorrentInvalid,
+        "required_account" => TaskStatus::ErrorRequiredAccount,
+        "try_it_later" => TaskStatus::ErrorTryItLater,
+        "task_encryption" => TaskStatus::ErrorEncryption,
+        "missing_python" => TaskStatus::ErrorMissingPython,
+        "private_video" => TaskStatus::ErrorPrivateVideo,
+        _ => TaskStatus::Error,
+    }
+}
+
+fn timestamp(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(seconds, 0).unwrap_or_default()
+}
+
+impl From<LegacyTask> for Task {
+    fn from(task: LegacyTask) -> Self {
+        let error_detail = task
+            .status_extra
+            .as_ref()
+            .and_then(|extra| extra.error_detail.as_deref());
+        let status = task.status.to_task_status(error_detail);
+        // Keep the raw name of an unknown status, as `TaskStatus::Unknown` only has room
+        // for a number
+        let status_name = match task.status {
+            LegacyStatus::Unknown(name) => Some(name),
+            _ => None,
+        };
+        let status_extra = match (task.status_extra, status_name) {
+            (None, None) => None,
+            (extra, status_name) => {
+                let extra = extra.unwrap_or_default();
+                Some(StatusExtra {
+                    error_detail: extra.error_detail,
+                    unzip_progress: extra.unzip_progress,
+                    status_name,
+                })
+            }
+        };
+
+        Task {
+            id: task.id,
+            username: task.username,
+            task_type: task.task_type,
+            title: task.title,
+            size: task.size,
+            status,
+            status_extra,
+            additional: task.additional.map(AdditionalTaskInfo::from),
+        }
+    }
+}
+
+impl From<LegacyAdditional> for AdditionalTaskInfo {
+    fn from(additional: LegacyAdditional) -> Self {
+        AdditionalTaskInfo {
+            detail: additional.detail.map(|detail| Detail {
+                completed_time: timestamp(detail.completed_time),
+                connected_leechers: detail.connected_leechers,
+                connected_peers: detail.connected_peers,
+                connected_seeders: detail.connected_seeders,
+                created_time: timestamp(detail.create_time),
+                destination: detail.destination,
+                seed_elapsed: detail.seedelapsed,
+                started_time: timestamp(detail.started_time),
+                total_peers: detail.total_peers,
+                total_pieces: detail.total_pieces,
+                uri: detail.uri,
+                unzip_password: detail.unzip_password,
+                waiting_seconds: detail.waiting_seconds,
+            }),
+            // The legacy API doesn't report file indexes, files are listed in index order
+            file: additional.file.map(|files| {
+                files
+                    .into_iter()
+                    .zip(0u32..)
+                    .map(|(file, index)| File {
+                        filename: file.filename,
+                        index,
+                        priority: file.priority,
+                        size: file.size,
+                        size_downloaded: file.size_downloaded,
+                        wanted: file.wanted,
+                    })
+                    .collect()
+            }),
+            peer: additional.peer,
+            tracker: additional.tracker,
+            transfer: additional.transfer.map(|transfer| Transfer {
+                downloaded_pieces: transfer.downloaded_pieces,
+                size_downloaded: transfer.size_downloaded,
+                size_uploaded: transfer.size_uploaded,
+                speed_download: transfer.speed_download,
+                speed_upload: transfer.speed_upload,
+            }),
+        }
+    }
+}
+
+impl From<Vec<LegacyOperationResult>> for TaskOperation {
+    fn from(results: Vec<LegacyOperationResult>) -> Self {
+        TaskOperation {
+            failed_task: results
+                .into_iter()
+                .filter(|result| result.error != 0)
+                .map(|result| FailedTask {
+                    error: result.error,
+                    id: result.id,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Formats the additional fields as the comma separated list used by the legacy API,
+/// `None` if there are none
+fn legacy_additional(fields: AdditionalFields) -> Option<String> {
+    if fields.is_empty() {
+        return None;
+    }
+
+    let fields = fields
+        .iter()
+        .map(AdditionalField::as_str)
+        .collect::<Vec<_>>()
+        .join(",");
+    Some(fields)
+}
+
+impl SynoDS {
+    /// Gets a page of tasks using the legacy API, all tasks if no limit is given
+    pub(super) async fn legacy_get_tasks(
+        &self,
+        offset: u32,
+        limit: Option<u32>,
+        additional: AdditionalFields,
+    ) -> Result<Tasks> {
+        let offset = offset.to_string();
+        let limit = limit.map_or_else(|| "-1".to_string(), |limit| limit.to_string());
+        let additional = legacy_additional(additional);
+
+        let mut params = vec![
+            ("method", "list"),
+            ("offset", offset.as_str()),
+            ("limit", limit.as_str()),
+        ];
+        if let Some(additional) = &additional {
+            params.push(("additional", additional));
+        }
+
+        let tasks: LegacyTasks = self
+            .api_call(&LEGACY_TASK_API, &params, "get tasks")
+            .await?;
+        Ok(Tasks {
+            offset: tasks.offset,
+            task: tasks.tasks.into_iter().map(Task::from).collect(),
+            total: tasks.total,
+        })
+    }
+
+    /// Gets detailed information about specific task(s) using the legacy API
+    pub(super) async fn legacy_get_task(
+        &self,
+        ids: &str,
+        additional: AdditionalFields,
+    ) -> Result<TaskInfo> {
+        let additional = legacy_additional(additional);
+
+        let mut params = vec![("method", "getinfo"), ("id", ids)];
+        if let Some(additional) = &additional {
+            params.push(("additional", additional));
+        }
+
+        let task_info: LegacyTaskInfo =
+            self.api_call(&LEGACY_TASK_API, &params, "get task").await?;
+        Ok(TaskInfo {
+            task: task_info.tasks.into_iter().map(Task::from).collect(),
+        })
+    }
+
+    /// Creates a new download task from a URI using the legacy API
+    pub(super) async fn legacy_create_task(
+        &self,
+        uri: &str,
+        destination: &str,
+        unzip_password: Option<&str>,
+    ) -> Result<TaskCreated> {
+        let mut params = vec![
+            ("method", "create"),
+            ("uri", uri),
+            ("destination", destination),
+        ];
+        if let Some(unzip_password) = unzip_password {
+            params.push(("unzip_password", unzip_password));
+        }
+
+        self.api_call::<()>(&LEGACY_TASK_API, &params, "create task")
+            .await?;
+        debug!("Successfully created download task for URI: {uri}");
+        // The legacy API doesn't report the IDs of created tasks
+        Ok(TaskCreated::default())
+    }
+
+    /// Creates a new download task from a torrent file using the legacy API
+    pub(super) async fn legacy_create_task_from_file(
+        &self,
+        file_data: &[u8],
+        file_name: &str,
+        destination: &str,
+        unzip_password: Option<&str>,
+    ) -> Result<TaskCreated> {
+        let response = self
+            .make_multipart_request::<()>(&LEGACY_TASK_API, |form| {
+                let file_part = Part::bytes(file_data.to_vec())
+                    .file_name(file_name.to_string())
+                    .mime_str("application/x-bittorrent")
+                    .context("Failed to create file part")?;
+
+                let mut form = form
+                    .text("method", "create")
+                    .text("destination", destination.to_string());
+                if let Some(unzip_password) = unzip_password {
+                    form = form.text("unzip_password", unzip_password.to_string());
+                }
+
+                Ok(form.part("file", file_part))
+            })
+            .await?;
+
+        if response.success {
+            debug!("Successfully created download task for file: {file_name}");
+            Ok(TaskCreated::default())
+        } else if let Some(error) = response.error {
+            Err(Api {
+                api: LEGACY_TASK_API.name.into(),
+                code: error.code,
+                message: "Failed to create task".into(),
+            }
+            .into())
+        } else {
+            Err(InvalidResponse("Failed to create task, unknown error".into()).into())
+        }
+    }
+
+    /// Runs one of the legacy `pause`, `resume` or `delete` methods
+    pub(super) async fn legacy_task_operation(
+        &self,
+        params: &[(&str, &str)],
+        action: &str,
+        ids: &str,
+    ) -> Result<TaskOperation> {
+        let results: Vec<LegacyOperationResult> = self
+            .api_call(
+                &LEGACY_TASK_API,
+                params,
+                &format!("{action} download task id: {ids}"),
+            )
+            .await?;
+        Ok(TaskOperation::from(results))
+    }
+
+    /// Runs one of the legacy `pause`, `resume` or `delete` methods on several tasks
+    pub(super) async fn legacy_batch_operation(
+        &self,
+        method: &str,
+        ids: &[&str],
+        extra_params: &[(&str, &str)],
+    ) -> Result<BatchResult> {
+        let id_string = ids.join(",");
+        let params = {
+            let mut params = vec![("method", method), ("id", id_string.as_str())];
+            params.extend_from_slice(extra_params);
+            params
+        };
+
+        let task_operation = self
+            .legacy_task_operation(&params, method, &id_string)
+            .await?;
+
+        Ok(BatchResult::new(ids, task_operation.failed_task))
+    }
+
+    /// Clears completed tasks using the legacy API, which has no `delete_condition` method
+    pub(super) async fn legacy_clear_completed(&self) -> Result<()> {
+        let tasks = self
+            .legacy_get_tasks(0, None, AdditionalFields::NONE)
+            .await
+            .context("Failed to clear completed tasks")?;
+
+        let finished_ids = tasks
+            .task
+            .iter()
+            .filter(|task| matches!(task.status, TaskStatus::Finished))
+            .map(|task| task.id.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        if finished_ids.is_empty() {
+            return Ok(());
+        }
+
+        let params = [
+            ("method", "delete"),
+            ("id", &finished_ids),
+            ("force_complete", "false"),
+        ];
+        let task_operation = self
+            .legacy_task_operation(&params, "delete", &finished_ids)
+            .await
+            .context("Failed to clear completed tasks")?;
+
+        match task_operation.failed_task.first() {
+            Some(failed_task) => Err(Api {
+                api: LEGACY_TASK_API.name.into(),
+                code: failed_task.error,
+                message: "Failed to clear completed tasks".into(),
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Gets the transfer speeds using the legacy `getinfo` method
+    pub(super) async fn legacy_get_statistics(&self) -> Result<TransferStatistics> {
+        let params = [("method", "getinfo")];
+
+        self.api_call(&LEGACY_STATISTIC_API, &params, "get transfer statistics")
+            .await
+    }
+}
@@ -0,0 +1,277 @@
+use crate::client::Backend::Legacy;
+use crate::client::SynoError::InvalidInput;
+use crate::client::{SynoDS, TASK_API};
+use crate::entities::{AdditionalFields, SortField, SortOrder, TaskStatus, Tasks};
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll, ready};
+use tokio_stream::Stream;
+
+/// Number of tasks fetched per page by [`TaskPages`] when no limit is set
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Request for a list of Download Station tasks, created with [`SynoDS::list_tasks`]
+///
+/// By default all tasks are requested with all additional fields.
+#[derive(Clone)]
+pub struct ListTasksRequest<'a> {
+    client: &'a SynoDS,
+    offset: u32,
+    limit: Option<u32>,
+    sort: Option<(SortField, SortOrder)>,
+    status: Vec<TaskStatus>,
+    additional: AdditionalFields,
+}
+
+impl<'a> ListTasksRequest<'a> {
+    fn new(client: &'a SynoDS) -> Self {
+        Self {
+            client,
+            offset: 0,
+            limit: None,
+            sort: None,
+            status: Vec::new(),
+            additional: AdditionalFields::ALL,
+        }
+    }
+
+    /// Sets the offset of the first task to return
+    #[must_use]
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the maximum number of tasks to return
+    #[must_use]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sorts the tasks by the given field. Not supported by the legacy backend.
+    #[must_use]
+    pub fn sort_by(mut self, field: SortField, order: SortOrder) -> Self {
+        self.sort = Some((field, order));
+        self
+    }
+
+    /// Returns only tasks in one of the given statuses. Not supported by the legacy backend.
+    #[must_use]
+    pub fn status(mut self, status: &[TaskStatus]) -> Self {
+        self.status = status.to_vec();
+        self
+    }
+
+    /// Sets the additional fields to fetch for every task
+    #[must_use]
+    pub fn additional(mut self, fields: impl Into<AdditionalFields>) -> Self {
+        self.additional = fields.into();
+        self
+    }
+
+    /// Fetches the requested tasks
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Sorting or status filter is used with the legacy backend
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn send(&self) -> Result<Tasks> {
+        self.fetch(self.offset, self.limit).await
+    }
+
+    /// Walks all requested tasks page by page, starting at the offset and using the limit
+    /// as page size (100 tasks if not set)
+    #[must_use]
+    pub fn pages(self) -> TaskPages<'a> {
+        TaskPages {
+            offset: self.offset,
+            page_size: self.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1),
+            request: self,
+            finished: false,
+        }
+    }
+
+    /// Fetches a single page of tasks
+    async fn fetch(&self, offset: u32, limit: Option<u32>) -> Result<Tasks> {
+        if self.client.backend().await? == Legacy {
+            if self.sort.is_some() || !self.status.is_empty() {
+                return Err(InvalidInput(
+                    "Sorting and status filters are not supported by the legacy backend".into(),
+                )
+                .into());
+            }
+            return self
+                .client
+                .legacy_get_tasks(offset, limit, self.additional)
+                .await;
+        }
+
+        let offset = offset.to_string();
+        let limit = limit.map(|limit| limit.to_string());
+        let sort = self.sort.map(|(field, order)| {
+            (
+                format!("\"{}\"", field.as_str()),
+                format!("\"{}\"", order.as_str()),
+            )
+        });
+        let status = format!(
+            "[{}]",
+            self.status
+                .iter()
+                .map(|status| status.code().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let additional = additional_param(self.additional);
+
+        let all_params = {
+            let mut params = vec![("method", "list"), ("offset", offset.as_str())];
+            if let Some(limit) = &limit {
+                params.push(("limit", limit));
+            }
+            if let Some((sort_by, order)) = &sort {
+                params.push(("sort_by", sort_by));
+                params.push(("order", order));
+            }
+            if !self.status.is_empty() {
+                params.push(("status", &status));
+            }
+            if let Some(additional) = &additional {
+                params.push(("additional", additional));
+            }
+            params
+        };
+
+        self.client
+            .api_call(&TASK_API, &all_params, "get tasks")
+            .await
+    }
+}
+
+/// Walks a task list page by page, created with [`ListTasksRequest::pages`]
+pub struct TaskPages<'a> {
+    request: ListTasksRequest<'a>,
+    offset: u32,
+    page_size: u32,
+    finished: bool,
+}
+
+impl<'a> TaskPages<'a> {
+    /// Fetches the next page of tasks, returns `None` once all tasks have been fetched
+    ///
+    /// Walking stops after the first error.
+    pub async fn next_page(&mut self) -> Option<Result<Tasks>> {
+        if self.finished {
+            return None;
+        }
+
+        let page = self.request.fetch(self.offset, Some(self.page_size)).await;
+        match &page {
+            Ok(tasks) => {
+                let fetched = u32::try_from(tasks.task.len()).unwrap_or(u32::MAX);
+                self.offset = self.offset.saturating_add(fetched);
+                self.finished = fetched == 0 || self.offset >= tasks.total;
+            }
+            Err(_) => self.finished = true,
+        }
+
+        Some(page)
+    }
+
+    /// Turns the pages into a stream, ending once all tasks have been fetched
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::SynoDS;
+    /// # use tokio_stream::StreamExt;
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// let mut pages = synods.list_tasks().limit(500).pages().into_stream();
+    /// while let Some(page) = pages.next().await {
+    ///     println!("{} tasks", page?.task.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn into_stream(self) -> TaskPageStream<'a> {
+        TaskPageStream {
+            next: Some(Box::pin(next_page(self))),
+        }
+    }
+}
+
+async fn next_page(mut pages: TaskPages<'_>) -> (Option<Result<Tasks>>, TaskPages<'_>) {
+    let page = pages.next_page().await;
+    (page, pages)
+}
+
+type NextPage<'a> =
+    Pin<Box<dyn Future<Output = (Option<Result<Tasks>>, TaskPages<'a>)> + Send + 'a>>;
+
+/// Stream of task pages, created with [`TaskPages::into_stream`]
+pub struct TaskPageStream<'a> {
+    next: Option<NextPage<'a>>,
+}
+
+impl Stream for TaskPageStream<'_> {
+    type Item = Result<Tasks>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        let Some(next) = self.next.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let (page, pages) = ready!(next.as_mut().poll(cx));
+        self.next = match page {
+            Some(_) => Some(Box::pin(next_page(pages))),
+            None => None,
+        };
+        Poll::Ready(page)
+    }
+}
+
+/// Formats the additional fields as a JSON array, `None` if there are none
+pub(super) fn additional_param(fields: AdditionalFields) -> Option<String> {
+    if fields.is_empty() {
+        return None;
+    }
+
+    let fields = fields
+        .iter()
+        .map(|field| format!("\"{}\"", field.as_str()))
+        .collect::<Vec<_>>()
+        .join(",");
+    Some(format!("[{fields}]"))
+}
+
+impl SynoDS {
+    /// Creates a request for a list of tasks with pagination, sorting, a status filter
+    /// and a selection of additional fields
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::SynoDS;
+    /// # use syno_download_station::entities::{AdditionalField, TaskStatus};
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// let mut pages = synods
+    ///     .list_tasks()
+    ///     .status(&[TaskStatus::Downloading])
+    ///     .additional(AdditionalField::Transfer)
+    ///     .limit(500)
+    ///     .pages();
+    /// while let Some(page) = pages.next_page().await {
+    ///     for task in page?.task {
+    ///         println!("{}: {}", task.id, task.title);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn list_tasks(&self) -> ListTasksRequest<'_> {
+        ListTasksRequest::new(self)
+    }
+}
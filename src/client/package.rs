@@ -0,0 +1,142 @@
+use crate::client::SynoError::ServiceNotRunning;
+use crate::client::{ApiSpec, SynoDS};
+use crate::entities::{PackageInfo, ServiceStatus};
+use anyhow::Result;
+use log::debug;
+use serde::Deserialize;
+
+const PACKAGE_INFO_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Package.Info",
+    min_version: 1,
+    max_version: 2,
+};
+
+const CORE_PACKAGE_API: ApiSpec = ApiSpec {
+    name: "SYNO.Core.Package",
+    min_version: 1,
+    max_version: 2,
+};
+
+const CORE_PACKAGE_CONTROL_API: ApiSpec = ApiSpec {
+    name: "SYNO.Core.Package.Control",
+    min_version: 1,
+    max_version: 1,
+};
+
+/// ID of the Download Station package in the package center
+const PACKAGE_ID: &str = "DownloadStation";
+
+/// Package returned by the `SYNO.Core.Package` `get` method
+#[derive(Deserialize, Debug)]
+struct CorePackage {
+    additional: CorePackageAdditional,
+}
+
+#[derive(Deserialize, Debug)]
+struct CorePackageAdditional {
+    status: ServiceStatus,
+}
+
+impl SynoDS {
+    /// Gets the version of the Download Station package, whether the user is a manager
+    /// and the default destination
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.Package.Info` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_package_info(&self) -> Result<PackageInfo> {
+        let all_params = [("method", "get")];
+
+        self.api_call(&PACKAGE_INFO_API, &all_params, "get package info")
+            .await
+    }
+
+    /// Gets whether the Download Station package is running
+    ///
+    /// Unlike the Download Station APIs, this keeps working while the package is
+    /// stopped. Requires an administrator account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.Core.Package`
+    /// - Network request fails
+    /// - API returns an error response, e.g. the user is not an administrator
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_service_status(&self) -> Result<ServiceStatus> {
+        let all_params = [
+            ("method", "get"),
+            ("id", PACKAGE_ID),
+            ("additional", "[\"status\"]"),
+        ];
+
+        let package: CorePackage = self
+            .api_call(&CORE_PACKAGE_API, &all_params, "get service status")
+            .await?;
+        Ok(package.additional.status)
+    }
+
+    /// Starts the Download Station package. Requires an administrator account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.Core.Package.Control`
+    /// - Network request fails
+    /// - API returns an error response, e.g. the user is not an administrator
+    /// - Session is invalid or expired
+    pub async fn start_service(&self) -> Result<()> {
+        self.control_service("start").await
+    }
+
+    /// Stops the Download Station package. Requires an administrator account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.Core.Package.Control`
+    /// - Network request fails
+    /// - API returns an error response, e.g. the user is not an administrator
+    /// - Session is invalid or expired
+    pub async fn stop_service(&self) -> Result<()> {
+        self.control_service("stop").await
+    }
+
+    /// Returns [`SynoError::ServiceNotRunning`] if `api` is a Download Station API and
+    /// the package is stopped. Used to explain a missing API, so a service status that
+    /// cannot be queried, e.g. without administrator rights, is ignored.
+    pub(crate) async fn ensure_service_running(&self, api: &str) -> Result<()> {
+        if !api.starts_with("SYNO.DownloadStation") {
+            return Ok(());
+        }
+
+        match Box::pin(self.get_service_status()).await {
+            Ok(ServiceStatus::Stopped) => Err(ServiceNotRunning { api: api.into() }.into()),
+            Ok(_) => Ok(()),
+            Err(error) => {
+                debug!("Failed to check whether Download Station is running: {error:#}");
+                Ok(())
+            }
+        }
+    }
+
+    /// Sends a `start` or `stop` request for the Download Station package
+    async fn control_service(&self, method: &str) -> Result<()> {
+        debug!("Sending {method} to the Download Station service");
+
+        let all_params = [("method", method), ("id", PACKAGE_ID)];
+
+        self.api_call(
+            &CORE_PACKAGE_CONTROL_API,
+            &all_params,
+            &format!("{method} service"),
+        )
+        .await
+    }
+}
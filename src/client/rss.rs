@@ -0,0 +1,289 @@
+use crate::client::SynoError::InvalidInput;
+use crate::client::{ApiSpec, SynoDS, json_string};
+use crate::entities::{RssFilter, RssFilters, RssItem, RssItems, RssSites, TaskCreated};
+use anyhow::{Context, Result};
+use log::debug;
+
+const RSS_SITE_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.RSS.Site",
+    min_version: 1,
+    max_version: 1,
+};
+
+const RSS_FILTER_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.RSS.Filter",
+    min_version: 1,
+    max_version: 1,
+};
+
+const RSS_ITEM_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.RSS.Item",
+    min_version: 1,
+    max_version: 1,
+};
+
+impl SynoDS {
+    /// Gets all subscribed RSS feeds
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Site` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_rss_sites(&self) -> Result<RssSites> {
+        let all_params = [("method", "list"), ("offset", "0"), ("limit", "-1")];
+        self.api_call(&RSS_SITE_API, &all_params, "get RSS feeds")
+            .await
+    }
+
+    /// Subscribes to an RSS feed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - URL doesn't start with http:// or https://
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Site` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn add_rss_site(&self, url: &str) -> Result<()> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(InvalidInput(format!(
+                "RSS feed URL must start with http:// or https://, got: {url}"
+            ))
+            .into());
+        }
+
+        debug!("Adding RSS feed {url}");
+
+        let url = json_string(url);
+        let all_params = [("method", "create"), ("url", url.as_str())];
+        self.api_call(&RSS_SITE_API, &all_params, "add RSS feed")
+            .await
+    }
+
+    /// Fetches new items of an RSS feed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Site` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn refresh_rss_site(&self, id: u32) -> Result<()> {
+        let id = id.to_string();
+        let all_params = [("method", "refresh"), ("id", id.as_str())];
+        self.api_call(&RSS_SITE_API, &all_params, "refresh RSS feed")
+            .await
+    }
+
+    /// Unsubscribes from an RSS feed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Site` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn delete_rss_site(&self, id: u32) -> Result<()> {
+        let id = id.to_string();
+        let all_params = [("method", "delete"), ("id", id.as_str())];
+        self.api_call(&RSS_SITE_API, &all_params, "delete RSS feed")
+            .await
+    }
+
+    /// Gets the items of an RSS feed, newest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Item` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_rss_items(&self, site_id: u32) -> Result<RssItems> {
+        let site_id = site_id.to_string();
+        let all_params = [
+            ("method", "list"),
+            ("rss_id", site_id.as_str()),
+            ("offset", "0"),
+            ("limit", "-1"),
+        ];
+        self.api_call(&RSS_ITEM_API, &all_params, "get RSS items")
+            .await
+    }
+
+    /// Creates a download task from an RSS item, see [`SynoDS::create_task`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The item's download URI or the destination is empty
+    /// - The item's download URI doesn't start with http://, https://, or magnet:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    pub async fn create_task_from_rss_item(
+        &self,
+        item: &RssItem,
+        destination: &str,
+    ) -> Result<TaskCreated> {
+        debug!("Creating download task for RSS item {}", item.title);
+
+        self.create_task(&item.download_uri, destination)
+            .await
+            .with_context(|| format!("Failed to download RSS item {}", item.title))
+    }
+
+    /// Gets the auto-download filters of an RSS feed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Filter` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_rss_filters(&self, site_id: u32) -> Result<RssFilters> {
+        let site_id = site_id.to_string();
+        let all_params = [
+            ("method", "list"),
+            ("rss_id", site_id.as_str()),
+            ("offset", "0"),
+            ("limit", "-1"),
+        ];
+        self.api_call(&RSS_FILTER_API, &all_params, "get RSS filters")
+            .await
+    }
+
+    /// Adds an auto-download filter to the feed of [`RssFilter::rss_id`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Filter name or destination is empty
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Filter` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn add_rss_filter(&self, filter: &RssFilter) -> Result<()> {
+        self.save_rss_filter("create", filter, "add RSS filter")
+            .await
+    }
+
+    /// Replaces the auto-download filter with the ID of [`RssFilter::id`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Filter ID is not set
+    /// - Filter name or destination is empty
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Filter` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn update_rss_filter(&self, filter: &RssFilter) -> Result<()> {
+        if filter.id == 0 {
+            return Err(InvalidInput("RSS filter ID must be set to update a filter".into()).into());
+        }
+
+        self.save_rss_filter("set", filter, "update RSS filter")
+            .await
+    }
+
+    /// Deletes an auto-download filter
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.RSS.Filter` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn delete_rss_filter(&self, id: u32) -> Result<()> {
+        let id = id.to_string();
+        let all_params = [("method", "delete"), ("id", id.as_str())];
+        self.api_call(&RSS_FILTER_API, &all_params, "delete RSS filter")
+            .await
+    }
+
+    /// Evaluates a filter against the current items of its feed without saving it,
+    /// returning the items it would download
+    ///
+    /// Matching is done locally with [`RssFilter::matches`].
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::SynoDS;
+    /// # use syno_download_station::entities::RssFilter;
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// let filter = RssFilter {
+    ///     rss_id: 1,
+    ///     name: "Show in 1080p".into(),
+    ///     match_pattern: "show 1080p".into(),
+    ///     not_match_pattern: "sample".into(),
+    ///     destination: "downloads/shows".into(),
+    ///     enabled: true,
+    ///     ..Default::default()
+    /// };
+    /// for item in synods.dry_run_rss_filter(&filter).await? {
+    ///     println!("Would download {}", item.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the items of the feed cannot be fetched
+    pub async fn dry_run_rss_filter(&self, filter: &RssFilter) -> Result<Vec<RssItem>> {
+        let items = self.get_rss_items(filter.rss_id).await?;
+
+        Ok(items
+            .item
+            .into_iter()
+            .filter(|item| filter.matches(item))
+            .collect())
+    }
+
+    /// Sends an RSS filter with the `create` or `set` method
+    async fn save_rss_filter(&self, method: &str, filter: &RssFilter, action: &str) -> Result<()> {
+        if filter.name.is_empty() {
+            return Err(InvalidInput("RSS filter name cannot be empty".into()).into());
+        }
+
+        if filter.destination.is_empty() {
+            return Err(InvalidInput("Destination path cannot be empty".into()).into());
+        }
+
+        debug!("Saving RSS filter {filter:?}");
+
+        let id = filter.id.to_string();
+        let rss_id = filter.rss_id.to_string();
+        let name = json_string(&filter.name);
+        let match_pattern = json_string(&filter.match_pattern);
+        let not_match_pattern = json_string(&filter.not_match_pattern);
+        let destination = json_string(&filter.destination);
+
+        let mut all_params = vec![("method", method)];
+        if method == "set" {
+            all_params.push(("id", &id));
+        }
+        all_params.extend([
+            ("rss_id", rss_id.as_str()),
+            ("name", &name),
+            ("match", &match_pattern),
+            ("not_match", &not_match_pattern),
+            ("destination", &destination),
+            ("enabled", if filter.enabled { "true" } else { "false" }),
+        ]);
+
+        self.api_call(&RSS_FILTER_API, &all_params, action).await
+    }
+}
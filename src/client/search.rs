@@ -0,0 +1,260 @@
+use crate::client::SynoError::{InvalidInput, Timeout};
+use crate::client::{ApiSpec, SynoDS, json_string};
+use crate::entities::{
+    BtSearchCategory, BtSearchModule, BtSearchResult, BtSearchResults, TaskCreated,
+};
+use anyhow::{Context, Result};
+use log::debug;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::time::Instant;
+
+const BT_SEARCH_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.BTSearch",
+    min_version: 1,
+    max_version: 1,
+};
+
+/// Time between two polls of [`BtSearch::wait`] when no interval is set
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time after which [`BtSearch::wait`] gives up when no timeout is set
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Response of the `start` method
+#[derive(Deserialize, Debug)]
+struct SearchStarted {
+    #[serde(alias = "taskid")]
+    id: String,
+}
+
+/// Response of the `list_category` method
+#[derive(Deserialize, Debug)]
+struct SearchCategories {
+    #[serde(default)]
+    categories: Vec<BtSearchCategory>,
+}
+
+/// Response of the `list_module` method
+#[derive(Deserialize, Debug)]
+struct SearchModules {
+    #[serde(default)]
+    modules: Vec<BtSearchModule>,
+}
+
+/// Running `BitTorrent` search, started with [`SynoDS::start_bt_search`]
+///
+/// The search keeps its results on the NAS until [`BtSearch::clean`] is called.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use syno_download_station::client::SynoDS;
+/// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+/// let search = synods
+///     .start_bt_search("ubuntu 24.04")
+///     .await?
+///     .poll_interval(Duration::from_secs(1))
+///     .timeout(Duration::from_secs(60));
+/// let results = search.wait().await?;
+/// if let Some(best) = results.items.iter().max_by_key(|result| result.seeds) {
+///     search.add_result(best, "downloads").await?;
+/// }
+/// search.clean().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BtSearch<'a> {
+    client: &'a SynoDS,
+    id: String,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl BtSearch<'_> {
+    /// ID of the search on the NAS
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Sets the time between two polls of [`BtSearch::wait`]
+    #[must_use]
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets how long [`BtSearch::wait`] polls before giving up, counted from the call to
+    /// `wait`, 5 minutes by default. A timeout too large to be represented as an instant,
+    /// e.g. `Duration::MAX`, means waiting without a deadline.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Gets the results found so far
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn poll(&self) -> Result<BtSearchResults> {
+        let id = json_string(&self.id);
+        let all_params = [
+            ("method", "list"),
+            ("id", id.as_str()),
+            ("offset", "0"),
+            ("limit", "-1"),
+        ];
+        self.client
+            .api_call(&BT_SEARCH_API, &all_params, "get search results")
+            .await
+    }
+
+    /// Polls the results until the search is finished or the timeout passes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The search doesn't finish within the timeout ([`SynoError::Timeout`])
+    /// - Polling fails, see [`BtSearch::poll`]
+    ///
+    /// [`SynoError::Timeout`]: crate::client::SynoError::Timeout
+    pub async fn wait(&self) -> Result<BtSearchResults> {
+        let Some(deadline) = Instant::now().checked_add(self.timeout) else {
+            return self.poll_until_finished().await;
+        };
+
+        tokio::time::timeout_at(deadline, self.poll_until_finished())
+            .await
+            .unwrap_or_else(|_| {
+                Err(Timeout(format!("Search {} did not finish in time", self.id)).into())
+            })
+    }
+
+    async fn poll_until_finished(&self) -> Result<BtSearchResults> {
+        loop {
+            let results = self.poll().await?;
+            if results.finished {
+                return Ok(results);
+            }
+
+            debug!(
+                "Search {} not finished, {} results so far",
+                self.id, results.total
+            );
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Creates a download task from a search result, see [`SynoDS::create_task`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the task cannot be created
+    pub async fn add_result(
+        &self,
+        result: &BtSearchResult,
+        destination: &str,
+    ) -> Result<TaskCreated> {
+        self.client
+            .create_task(&result.download_uri, destination)
+            .await
+            .with_context(|| format!("Failed to download search result {}", result.title))
+    }
+
+    /// Removes the search and its results from the NAS
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn clean(self) -> Result<()> {
+        let id = json_string(&self.id);
+        let all_params = [("method", "clean"), ("id", id.as_str())];
+
+        self.client
+            .api_call(&BT_SEARCH_API, &all_params, "clean search")
+            .await
+    }
+}
+
+impl SynoDS {
+    /// Starts a `BitTorrent` search with all enabled search modules
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Keyword is empty
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.BTSearch` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn start_bt_search(&self, keyword: &str) -> Result<BtSearch<'_>> {
+        if keyword.trim().is_empty() {
+            return Err(InvalidInput("Search keyword cannot be empty".into()).into());
+        }
+
+        debug!("Starting search for {keyword}");
+
+        let keyword = json_string(keyword);
+        let all_params = [
+            ("method", "start"),
+            ("keyword", keyword.as_str()),
+            ("module", "\"enabled\""),
+        ];
+        let started: SearchStarted = self
+            .api_call(&BT_SEARCH_API, &all_params, "start search")
+            .await?;
+
+        Ok(BtSearch {
+            client: self,
+            id: started.id,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Gets the categories searches can be limited to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.BTSearch` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_bt_search_categories(&self) -> Result<Vec<BtSearchCategory>> {
+        let all_params = [("method", "list_category")];
+        let categories: SearchCategories = self
+            .api_call(&BT_SEARCH_API, &all_params, "get search categories")
+            .await?;
+        Ok(categories.categories)
+    }
+
+    /// Gets the sites searches are run on
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide `SYNO.DownloadStation2.BTSearch` (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_bt_search_modules(&self) -> Result<Vec<BtSearchModule>> {
+        let all_params = [("method", "list_module")];
+        let modules: SearchModules = self
+            .api_call(&BT_SEARCH_API, &all_params, "get search modules")
+            .await?;
+        Ok(modules.modules)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::client::SynoError::InvalidInput;
+use crate::client::{ApiSpec, SynoDS};
+use crate::settings::{Settings, SettingsUpdate};
+use anyhow::{Context, Result};
+use log::debug;
+use serde_json::Value;
+
+/// Spec of a `SYNO.DownloadStation2.Settings.*` API, all sections are used at version 1
+const fn settings_api(name: &'static str) -> ApiSpec {
+    ApiSpec {
+        name,
+        min_version: 1,
+        max_version: 1,
+    }
+}
+
+impl SynoDS {
+    /// Gets a section of the global Download Station settings
+    ///
+    /// ```rust,no_run
+    /// # use syno_download_station::client::SynoDS;
+    /// # use syno_download_station::settings::GlobalSettings;
+    /// # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+    /// let global = synods.get_settings::<GlobalSettings>().await?;
+    /// println!("Default destination: {}", global.default_destination);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The NAS doesn't provide the settings API (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_settings<S: Settings>(&self) -> Result<S> {
+        let spec = settings_api(S::API_NAME);
+        let all_params = [("method", "get")];
+
+        self.api_call(&spec, &all_params, &format!("get {} settings", S::API_NAME))
+            .await
+    }
+
+    /// Changes the fields set in the update, leaving the other settings of the section
+    /// unchanged
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The update doesn't change any field
+    /// - The NAS doesn't provide the settings API (legacy backend)
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Session is invalid or expired
+    pub async fn update_settings<U: SettingsUpdate>(&self, update: &U) -> Result<()> {
+        let api_name = U::Settings::API_NAME;
+        let spec = settings_api(api_name);
+
+        let Value::Object(fields) =
+            serde_json::to_value(update).context("Failed to serialize settings")?
+        else {
+            return Err(InvalidInput("Settings update must be a struct".into()).into());
+        };
+        let values = fields
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(name, value)| (name, value.to_string()))
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            return Err(InvalidInput("Settings update cannot be empty".into()).into());
+        }
+
+        // Only the names are logged, values may hold secrets such as the NZB password
+        let names = values
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        debug!("Updating {api_name} fields: {}", names.join(", "));
+
+        let mut all_params = vec![("method", "set")];
+        all_params.extend(
+            values
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        );
+
+        self.api_call(&spec, &all_params, &format!("update {api_name} settings"))
+            .await
+    }
+}
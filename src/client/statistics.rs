@@ -0,0 +1,35 @@
+use crate::client::Backend::Legacy;
+use crate::client::{ApiSpec, SynoDS};
+use crate::entities::TransferStatistics;
+use anyhow::Result;
+
+const STATISTIC_API: ApiSpec = ApiSpec {
+    name: "SYNO.DownloadStation2.Task.Statistic",
+    min_version: 1,
+    max_version: 1,
+};
+
+impl SynoDS {
+    /// Gets the total download and upload speeds of all tasks, including protocol
+    /// overhead, in a single request
+    ///
+    /// Use [`crate::entities::Tasks::summary`] for totals calculated from a task list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network request fails
+    /// - API returns an error response
+    /// - Response cannot be parsed
+    /// - Session is invalid or expired
+    pub async fn get_statistics(&self) -> Result<TransferStatistics> {
+        if self.backend().await? == Legacy {
+            return self.legacy_get_statistics().await;
+        }
+
+        let all_params = [("method", "get")];
+
+        self.api_call(&STATISTIC_API, &all_params, "get transfer statistics")
+            .await
+    }
+}
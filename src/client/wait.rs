@@ -0,0 +1,196 @@
+use crate::client::SynoDS;
+use crate::client::SynoError::{self, Cancelled, TaskFailed, TaskNotFound, Timeout};
+use crate::entities::{AdditionalField, Task};
+use crate::error_code::ErrorCode;
+use anyhow::Result;
+use log::debug;
+use std::future::{Future, pending};
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Shortest time between two polls when no minimum is set
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest time between two polls when no maximum is set
+const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(15);
+
+type CancelSignal<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// Waits until a task has finished downloading, created with
+/// [`SynoDS::wait_for_completion`]
+///
+/// The task is polled more often as its estimated time left shrinks, between the
+/// minimum and maximum interval. Polls failing with a retryable error are repeated.
+/// Dropping the future returned by [`WaitForCompletion::wait`] also stops waiting.
+pub struct WaitForCompletion<'a> {
+    client: &'a SynoDS,
+    task_id: String,
+    min_interval: Duration,
+    max_interval: Duration,
+    deadline: Option<Instant>,
+    cancel: Option<CancelSignal<'a>>,
+}
+
+impl<'a> WaitForCompletion<'a> {
+    fn new(client: &'a SynoDS, task_id: &str) -> Self {
+        Self {
+            client,
+            task_id: task_id.to_string(),
+            min_interval: DEFAULT_MIN_INTERVAL,
+            max_interval: DEFAULT_MAX_INTERVAL,
+            deadline: None,
+            cancel: None,
+        }
+    }
+
+    /// Sets the shortest time between two polls, 1 second by default
+    #[must_use]
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Sets the longest time between two polls, 15 seconds by default. Also used while
+    /// the time left is unknown.
+    #[must_use]
+    pub fn max_interval(mut self, interval: Duration) -> Self {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Gives up after the given time, counted from now. A timeout too large to be
+    /// represented as an instant, e.g. `Duration::MAX`, means waiting without a deadline.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Instant::now().checked_add(timeout);
+        self
+    }
+
+    /// Gives up at the given instant
+    #[must_use]
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Gives up as soon as the given future completes, e.g. a `oneshot` receiver or
+    /// `tokio::signal::ctrl_c()`
+    #[must_use]
+    pub fn cancel_on(mut self, signal: impl Future<Output = ()> + Send + 'a) -> Self {
+        self.cancel = Some(Box::pin(signal));
+        self
+    }
+
+    /// Polls the task until it is finished or seeding and returns its last state
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The task enters an error status ([`SynoError::TaskFailed`])
+    /// - The deadline passes ([`SynoError::Timeout`])
+    /// - The cancel signal completes ([`SynoError::Cancelled`])
+    /// - The task doesn't exist ([`SynoError::TaskNotFound`])
+    /// - A poll fails with an error that is not retryable
+    pub async fn wait(mut self) -> Result<Task> {
+        let cancel = self.cancel.take();
+        let deadline = self.deadline;
+
+        tokio::select! {
+            result = self.poll_until_done() => result,
+            () = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => pending().await,
+                }
+            } => Err(Timeout(format!("Task {} did not complete in time", self.task_id)).into()),
+            () = async {
+                match cancel {
+                    Some(cancel) => cancel.await,
+                    None => pending().await,
+                }
+            } => Err(Cancelled(format!("Stopped waiting for task {}", self.task_id)).into()),
+        }
+    }
+
+    async fn poll_until_done(&self) -> Result<Task> {
+        loop {
+            let interval = match self
+                .client
+                .get_task_with(vec![self.task_id.clone()], AdditionalField::Transfer)
+                .await
+            {
+                Ok(info) => {
+                    let Some(task) = info.task.into_iter().find(|task| task.id == self.task_id)
+                    else {
+                        return Err(TaskNotFound(self.task_id.clone()).into());
+                    };
+
+                    if task.status.is_completed() {
+                        return Ok(task);
+                    }
+                    if task.status.is_error() {
+                        return Err(TaskFailed {
+                            task_id: task.id,
+                            status: task.status,
+                            error_detail: task.status_extra.and_then(|extra| extra.error_detail),
+                        }
+                        .into());
+                    }
+                    self.next_interval(&task)
+                }
+                // DS2 answers `get` for an unknown ID with "invalid task id" instead of
+                // an empty list
+                Err(error)
+                    if error.downcast_ref::<SynoError>().is_some_and(|error| {
+                        error.error_code() == Some(ErrorCode::InvalidTaskId)
+                    }) =>
+                {
+                    return Err(TaskNotFound(self.task_id.clone()).into());
+                }
+                Err(error)
+                    if error
+                        .downcast_ref::<SynoError>()
+                        .is_some_and(SynoError::is_retryable) =>
+                {
+                    debug!("Retrying poll of task {}: {error:#}", self.task_id);
+                    self.max_interval
+                }
+                Err(error) => return Err(error),
+            };
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Half the estimated time left, within the minimum and maximum interval
+    fn next_interval(&self, task: &Task) -> Duration {
+        let max_interval = self.max_interval.max(self.min_interval);
+        task.time_left().map_or(max_interval, |time_left| {
+            (time_left / 2).clamp(self.min_interval, max_interval)
+        })
+    }
+}
+
+impl SynoDS {
+    /// Creates a helper waiting until the task has finished downloading
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use syno_download_station::client::SynoDS;
+    /// # async fn example(synods: &SynoDS, magnet: &str) -> anyhow::Result<()> {
+    /// let created = synods.create_task(magnet, "downloads").await?;
+    /// let task = synods
+    ///     .wait_for_completion(&created.task_id[0])
+    ///     .timeout(Duration::from_secs(3600))
+    ///     .wait()
+    ///     .await?;
+    /// println!("{} is done", task.title);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn wait_for_completion(&self, task_id: &str) -> WaitForCompletion<'_> {
+        WaitForCompletion::new(self, task_id)
+    }
+}
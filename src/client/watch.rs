@@ -0,0 +1,315 @@
+use crate::client::SynoDS;
+use crate::entities::{AdditionalField, Task, TaskStatus};
+use anyhow::Result;
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+use tokio_stream::Stream;
+
+/// Time between two polls when no interval is set
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest time between two polls while the NAS keeps failing
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Progress percentages reported when no milestones are set
+const DEFAULT_MILESTONES: [u8; 3] = [25, 50, 75];
+
+/// Change of a task spotted by a [`TaskWatcher`]
+///
+/// Every event carries the task as seen by the poll that spotted the change, the
+/// last seen version for removed tasks.
+#[derive(Debug, Clone)]
+pub enum TaskEvent {
+    /// A task appeared since the previous poll
+    TaskAdded(Task),
+    /// A task disappeared since the previous poll
+    TaskRemoved(Task),
+    /// The status of a task changed
+    StatusChanged {
+        task: Task,
+        from: TaskStatus,
+        to: TaskStatus,
+    },
+    /// The download progress passed one of the watched percentages
+    ProgressMilestone { task: Task, percent: u8 },
+    /// A task finished downloading, it is now finished or seeding
+    Completed(Task),
+    /// A task entered one of the error statuses
+    Errored {
+        task: Task,
+        /// Error detail reported by the NAS, see [`crate::entities::StatusExtra`]
+        error_detail: Option<String>,
+    },
+}
+
+/// Watches the task list for changes, created with [`SynoDS::watch_tasks`]
+///
+/// The first poll only records the current tasks, events are reported for the changes
+/// seen by later polls. Every poll walks the whole task list page by page, see
+/// [`TaskWatcher::page_size`]. When a poll fails, the error is returned and the time until the
+/// next poll doubles, up to the maximum backoff, until a poll succeeds again.
+pub struct TaskWatcher<'a> {
+    client: &'a SynoDS,
+    interval: Duration,
+    max_backoff: Duration,
+    page_size: Option<u32>,
+    milestones: Vec<u8>,
+    snapshots: Option<HashMap<String, Task>>,
+    pending: VecDeque<TaskEvent>,
+    failures: u32,
+    polled: bool,
+}
+
+impl<'a> TaskWatcher<'a> {
+    fn new(client: &'a SynoDS) -> Self {
+        Self {
+            client,
+            interval: DEFAULT_INTERVAL,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            page_size: None,
+            milestones: DEFAULT_MILESTONES.to_vec(),
+            snapshots: None,
+            pending: VecDeque::new(),
+            failures: 0,
+            polled: false,
+        }
+    }
+
+    /// Sets the time between two polls, 10 seconds by default
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the longest time between two polls while polls fail, 5 minutes by default
+    #[must_use]
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the number of tasks fetched per request of a poll, 100 by default, see
+    /// [`crate::client::ListTasksRequest::pages`]
+    #[must_use]
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Sets the progress percentages reported with [`TaskEvent::ProgressMilestone`],
+    /// 25, 50 and 75 by default
+    #[must_use]
+    pub fn milestones(mut self, percents: &[u8]) -> Self {
+        let mut milestones = percents.to_vec();
+        milestones.sort_unstable();
+        milestones.dedup();
+        self.milestones = milestones;
+        self
+    }
+
+    /// Waits for the next change, polling the task list as often as needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a poll fails, the next call polls again after the backoff
+    pub async fn next_event(&mut self) -> Result<TaskEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            if self.polled {
+                tokio::time::sleep(self.delay()).await;
+            }
+            self.polled = true;
+
+            match self.poll().await {
+                Ok(tasks) => {
+                    self.failures = 0;
+                    self.update(tasks);
+                }
+                Err(error) => {
+                    self.failures = self.failures.saturating_add(1);
+                    warn!(
+                        "Failed to poll tasks ({} in a row): {error:#}",
+                        self.failures
+                    );
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    /// Turns the watcher into a never ending stream of events
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use syno_download_station::client::{SynoDS, TaskEvent};
+    /// # use tokio_stream::StreamExt;
+    /// # async fn example(synods: &SynoDS) {
+    /// let mut events = synods
+    ///     .watch_tasks()
+    ///     .interval(Duration::from_secs(30))
+    ///     .into_stream();
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         Ok(TaskEvent::Completed(task)) => println!("{} finished", task.title),
+    ///         Ok(TaskEvent::Errored { task, error_detail }) => {
+    ///             println!("{} failed: {error_detail:?}", task.title);
+    ///         }
+    ///         Ok(_) => {}
+    ///         Err(error) => println!("NAS unreachable: {error}"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn into_stream(self) -> TaskEventStream<'a> {
+        TaskEventStream {
+            next: Box::pin(next_event(self)),
+        }
+    }
+
+    /// Fetches all tasks, page by page
+    async fn poll(&self) -> Result<Vec<Task>> {
+        let mut request = self
+            .client
+            .list_tasks()
+            .additional(AdditionalField::Transfer);
+        if let Some(page_size) = self.page_size {
+            request = request.limit(page_size);
+        }
+
+        let mut pages = request.pages();
+        let mut tasks = Vec::new();
+        while let Some(page) = pages.next_page().await {
+            tasks.extend(page?.task);
+        }
+        Ok(tasks)
+    }
+
+    /// Time to wait before the next poll, doubled for every failed poll in a row
+    fn delay(&self) -> Duration {
+        if self.failures == 0 {
+            return self.interval;
+        }
+
+        let factor = 2u32.saturating_pow(self.failures.min(16));
+        self.interval
+            .saturating_mul(factor)
+            .min(self.max_backoff.max(self.interval))
+    }
+
+    /// Compares the polled tasks with the previous snapshot and queues the changes
+    fn update(&mut self, tasks: Vec<Task>) {
+        let Some(snapshots) = self.snapshots.as_mut() else {
+            debug!("Watching {} tasks", tasks.len());
+            self.snapshots = Some(
+                tasks
+                    .into_iter()
+                    .map(|task| (task.id.clone(), task))
+                    .collect(),
+            );
+            return;
+        };
+
+        let current_ids = tasks
+            .iter()
+            .map(|task| task.id.clone())
+            .collect::<HashSet<_>>();
+        let mut removed = snapshots
+            .iter()
+            .filter(|(id, _)| !current_ids.contains(*id))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        removed.sort();
+
+        for task in tasks {
+            match snapshots.get(&task.id) {
+                None => self.pending.push_back(TaskEvent::TaskAdded(task.clone())),
+                Some(previous) => {
+                    changes(previous, &task, &self.milestones, &mut self.pending);
+                }
+            }
+            snapshots.insert(task.id.clone(), task);
+        }
+
+        for id in removed {
+            if let Some(task) = snapshots.remove(&id) {
+                self.pending.push_back(TaskEvent::TaskRemoved(task));
+            }
+        }
+    }
+}
+
+/// Queues the events for the changes between two versions of a task
+fn changes(previous: &Task, task: &Task, milestones: &[u8], events: &mut VecDeque<TaskEvent>) {
+    let (from, to) = (previous.status, task.status);
+    if from != to {
+        events.push_back(TaskEvent::StatusChanged {
+            task: task.clone(),
+            from,
+            to,
+        });
+        if to.is_completed() && !from.is_completed() {
+            events.push_back(TaskEvent::Completed(task.clone()));
+        }
+        if to.is_error() && !from.is_error() {
+            events.push_back(TaskEvent::Errored {
+                task: task.clone(),
+                error_detail: task
+                    .status_extra
+                    .as_ref()
+                    .and_then(|extra| extra.error_detail.clone()),
+            });
+        }
+    }
+
+    let (before, after) = (previous.calculate_progress(), task.calculate_progress());
+    for &percent in milestones {
+        let percent_value = f64::from(percent);
+        if before < percent_value && after >= percent_value {
+            events.push_back(TaskEvent::ProgressMilestone {
+                task: task.clone(),
+                percent,
+            });
+        }
+    }
+}
+
+async fn next_event(mut watcher: TaskWatcher<'_>) -> (Result<TaskEvent>, TaskWatcher<'_>) {
+    let event = watcher.next_event().await;
+    (event, watcher)
+}
+
+type NextEvent<'a> =
+    Pin<Box<dyn Future<Output = (Result<TaskEvent>, TaskWatcher<'a>)> + Send + 'a>>;
+
+/// Stream of task changes, created with [`TaskWatcher::into_stream`]
+pub struct TaskEventStream<'a> {
+    next: NextEvent<'a>,
+}
+
+impl Stream for TaskEventStream<'_> {
+    type Item = Result<TaskEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (event, watcher) = ready!(self.next.as_mut().poll(cx));
+        self.next = Box::pin(next_event(watcher));
+        Poll::Ready(Some(event))
+    }
+}
+
+impl SynoDS {
+    /// Creates a watcher polling the task list and reporting added and removed tasks,
+    /// status changes, progress milestones, completed and failed tasks
+    #[must_use]
+    pub fn watch_tasks(&self) -> TaskWatcher<'_> {
+        TaskWatcher::new(self)
+    }
+}
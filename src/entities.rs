@@ -1,7 +1,10 @@
+use crate::error_code::ErrorCode;
 use chrono::serde::ts_seconds;
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::BitOr;
 
 /// Response from Synology API
 #[derive(Deserialize, Debug)]
@@ -11,13 +14,26 @@ pub struct SynologyResponse<D> {
     pub error: Option<SynoError>,
 }
 
+/// Description of a Web API as reported by `SYNO.API.Info`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiInfo {
+    /// Path of the CGI handling the API, relative to `/webapi/`
+    pub path: String,
+    pub min_version: u32,
+    pub max_version: u32,
+    #[serde(default)]
+    pub request_format: Option<String>,
+}
+
 /// Authentication response data
 #[allow(unused)]
 #[derive(Deserialize, Debug)]
 pub struct AuthData {
     #[serde(default)]
     pub account: String,
-    #[serde(default)]
+    /// Device ID of a trusted device, returned when a device token is requested
+    #[serde(default, alias = "did")]
     pub device_id: String,
     #[serde(default)]
     pub ik_message: String,
@@ -32,9 +48,11 @@ pub struct AuthData {
 /// Collection of download tasks
 #[derive(Deserialize, Debug)]
 pub struct Tasks {
-    pub offset: i8,
+    /// Offset of the first returned task
+    pub offset: u32,
     pub task: Vec<Task>,
-    pub total: i32,
+    /// Total number of tasks matching the request, regardless of the limit
+    pub total: u32,
 }
 
 /// Detailed information about specific tasks
@@ -44,7 +62,7 @@ pub struct TaskInfo {
 }
 
 /// Individual download task information
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Task {
     /// Unique identifier for the task
     pub id: String,
@@ -65,24 +83,37 @@ pub struct Task {
 }
 
 /// Extra task details
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct StatusExtra {
     pub error_detail: Option<String>,
     pub unzip_progress: Option<i32>,
+    /// Raw status name reported by the legacy API when it maps to
+    /// [`TaskStatus::Unknown`], always `None` for `SYNO.DownloadStation2.Task`
+    #[serde(default)]
+    pub status_name: Option<String>,
 }
 
 /// Additional detailed information about a task
-#[derive(Deserialize, Default, Debug)]
+///
+/// Only the blocks requested with [`AdditionalFields`] are populated, the others are
+/// `None`. The NAS may also leave out blocks that don't apply to a task, for example
+/// `peer` and `tracker` for HTTP downloads.
+#[derive(Deserialize, Default, Debug, Clone)]
 pub struct AdditionalTaskInfo {
+    /// Populated when [`AdditionalField::Detail`] is requested
     pub detail: Option<Detail>,
+    /// Populated when [`AdditionalField::File`] is requested
     pub file: Option<Vec<File>>,
+    /// Populated when [`AdditionalField::Peer`] is requested
     pub peer: Option<Vec<Peer>>,
+    /// Populated when [`AdditionalField::Tracker`] is requested
     pub tracker: Option<Vec<Tracker>>,
+    /// Populated when [`AdditionalField::Transfer`] is requested
     pub transfer: Option<Transfer>,
 }
 
 /// Detailed task information
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Detail {
     #[serde(with = "ts_seconds")]
     pub completed_time: DateTime<Utc>,
@@ -103,18 +134,65 @@ pub struct Detail {
 }
 
 /// Information about a file within a download task
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct File {
     pub filename: String,
     pub index: u32,
-    pub priority: String,
+    pub priority: FilePriority,
     pub size: u64,
     pub size_downloaded: u64,
     pub wanted: bool,
 }
 
+/// Bandwidth and seeding limits of a single `BitTorrent` task
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TaskLimits {
+    /// Maximum download rate in KB/s, 0 for unlimited
+    #[serde(default)]
+    pub max_download_rate: u32,
+    /// Maximum upload rate in KB/s, 0 for unlimited
+    #[serde(default)]
+    pub max_upload_rate: u32,
+    /// Share ratio after which seeding stops, 0 for no limit
+    #[serde(default)]
+    pub seeding_ratio: f64,
+    /// Minutes after which seeding stops, 0 for no limit
+    #[serde(default)]
+    pub seeding_interval: u32,
+}
+
+/// Download priority of a file within a `BitTorrent` task
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FilePriority {
+    /// The file is not downloaded
+    Skip,
+    Low,
+    /// Default priority, reported as `auto` by the legacy API
+    #[serde(alias = "auto")]
+    Normal,
+    High,
+    /// Priority not known to this client
+    #[serde(other)]
+    Unknown,
+}
+
+impl FilePriority {
+    /// Name of the priority in the API
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FilePriority::Skip => "skip",
+            FilePriority::Low => "low",
+            FilePriority::Normal => "normal",
+            FilePriority::High => "high",
+            FilePriority::Unknown => "unknown",
+        }
+    }
+}
+
 /// Information about a connected peer
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Peer {
     pub address: String,
     pub agent: String,
@@ -124,7 +202,7 @@ pub struct Peer {
 }
 
 /// Information about a tracker
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Tracker {
     pub peers: i32,
     pub seeds: i32,
@@ -134,7 +212,7 @@ pub struct Tracker {
 }
 
 /// Transfer statistics
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Debug, Clone)]
 pub struct Transfer {
     pub downloaded_pieces: u32,
     pub size_downloaded: u64,
@@ -143,59 +221,534 @@ pub struct Transfer {
     pub speed_upload: u64,
 }
 
-/// Download task status enum
-#[derive(Serialize_repr, Deserialize_repr, Debug)]
-#[repr(u8)]
+/// Block of additional task information that can be requested along with a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdditionalField {
+    Detail,
+    File,
+    Peer,
+    Tracker,
+    Transfer,
+}
+
+impl AdditionalField {
+    /// Name of the field in the API
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AdditionalField::Detail => "detail",
+            AdditionalField::File => "file",
+            AdditionalField::Peer => "peer",
+            AdditionalField::Tracker => "tracker",
+            AdditionalField::Transfer => "transfer",
+        }
+    }
+}
+
+/// Set of [`AdditionalField`]s to request along with tasks
+///
+/// Sets are built from single fields, arrays or with `|`:
+///
+/// ```rust
+/// # use syno_download_station::entities::{AdditionalField, AdditionalFields};
+/// let fields = AdditionalField::Transfer | AdditionalField::Detail;
+/// assert!(fields.contains(AdditionalField::Transfer));
+/// assert_eq!(fields, AdditionalFields::from([AdditionalField::Detail, AdditionalField::Transfer]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AdditionalFields(u8);
+
+impl AdditionalFields {
+    /// No additional fields
+    pub const NONE: AdditionalFields = AdditionalFields(0);
+
+    /// All additional fields
+    pub const ALL: AdditionalFields = AdditionalFields(0b1_1111);
+
+    /// Order in which the fields are sent to the API
+    const ORDER: [AdditionalField; 5] = [
+        AdditionalField::Transfer,
+        AdditionalField::Tracker,
+        AdditionalField::Peer,
+        AdditionalField::File,
+        AdditionalField::Detail,
+    ];
+
+    /// Returns the set with the given field added
+    #[must_use]
+    pub const fn with(self, field: AdditionalField) -> Self {
+        AdditionalFields(self.0 | Self::bit(field))
+    }
+
+    /// Returns the set with the given field removed
+    #[must_use]
+    pub const fn without(self, field: AdditionalField) -> Self {
+        AdditionalFields(self.0 & !Self::bit(field))
+    }
+
+    /// Whether the set contains the given field
+    #[must_use]
+    pub const fn contains(self, field: AdditionalField) -> bool {
+        self.0 & Self::bit(field) != 0
+    }
+
+    /// Whether the set contains no fields
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterates over the fields in the set
+    pub fn iter(self) -> impl Iterator<Item = AdditionalField> {
+        Self::ORDER
+            .into_iter()
+            .filter(move |field| self.contains(*field))
+    }
+
+    const fn bit(field: AdditionalField) -> u8 {
+        1 << field as u8
+    }
+}
+
+impl From<AdditionalField> for AdditionalFields {
+    fn from(field: AdditionalField) -> Self {
+        AdditionalFields::NONE.with(field)
+    }
+}
+
+impl<const N: usize> From<[AdditionalField; N]> for AdditionalFields {
+    fn from(fields: [AdditionalField; N]) -> Self {
+        fields.into_iter().collect()
+    }
+}
+
+impl FromIterator<AdditionalField> for AdditionalFields {
+    fn from_iter<I: IntoIterator<Item = AdditionalField>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(AdditionalFields::NONE, AdditionalFields::with)
+    }
+}
+
+impl BitOr for AdditionalFields {
+    type Output = AdditionalFields;
+
+    fn bitor(self, rhs: AdditionalFields) -> AdditionalFields {
+        AdditionalFields(self.0 | rhs.0)
+    }
+}
+
+impl BitOr<AdditionalField> for AdditionalFields {
+    type Output = AdditionalFields;
+
+    fn bitor(self, rhs: AdditionalField) -> AdditionalFields {
+        self.with(rhs)
+    }
+}
+
+impl BitOr for AdditionalField {
+    type Output = AdditionalFields;
+
+    fn bitor(self, rhs: AdditionalField) -> AdditionalFields {
+        AdditionalFields::from(self).with(rhs)
+    }
+}
+
+/// Field to sort the task list by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Title,
+    Size,
+    Status,
+    Type,
+    CreatedTime,
+    WaitingSeconds,
+}
+
+impl SortField {
+    /// Name of the field in the API
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortField::Title => "title",
+            SortField::Size => "size",
+            SortField::Status => "status",
+            SortField::Type => "type",
+            SortField::CreatedTime => "created_time",
+            SortField::WaitingSeconds => "waiting_seconds",
+        }
+    }
+}
+
+/// Direction to sort the task list in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    /// Name of the order in the API
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "ASC",
+            SortOrder::Descending => "DESC",
+        }
+    }
+}
+
+/// Download task status
+///
+/// Statuses are sent as numbers, numbers not listed here are kept as [`TaskStatus::Unknown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TaskStatus {
-    Waiting = 1,
-    Downloading = 2,
-    Paused = 3,
-    Finishing = 4,
-    Finished = 5,
-    HashChecking = 6,
-    PreSeeding = 7,
-    Seeding = 8,
-    FilehostingWaiting = 9,
-    Extracting = 10,
-    Preprocessing = 11,
-    PreprocessPass = 12,
-    Downloaded = 13,
-    Postprocessing = 14,
-    CaptchaNeeded = 15,
-    Error = 101,
-    ErrorBrokenLink = 102,
-    ErrorDestNoExist = 103,
-    ErrorDestDeny = 104,
-    ErrorDiskFull = 105,
-    ErrorQuotaReached = 106,
-    ErrorTimeout = 107,
-    ErrorExceedMaxFsSize = 108,
-    ErrorExceedMaxTempFsSize = 109,
-    ErrorExceedMaxDestFsSize = 110,
-    ErrorNameTooLongEncryption = 111,
-    ErrorNameTooLong = 112,
-    ErrorTorrentDuplicate = 113,
-    ErrorFileNoExist = 114,
-    ErrorRequiredPremium = 115,
-    ErrorNotSupportType = 116,
-    ErrorFtpEncryptionNotSupportType = 117,
-    ErrorExtractFail = 118,
-    ErrorExtractWrongPassword = 119,
-    ErrorExtractInvalidArchive = 120,
-    ErrorExtractQuotaReached = 121,
-    ErrorExtractDiskFull = 122,
-    ErrorTorrentInvalid = 123,
-    ErrorRequiredAccount = 124,
-    ErrorTryItLater = 125,
-    ErrorEncryption = 126,
-    ErrorMissingPython = 127,
-    ErrorPrivateVideo = 128,
-    ErrorExtractFolderNotExist = 129,
-    ErrorNzbMissingArticle = 130,
-    ErrorEd2KLinkDuplicate = 131,
-    ErrorDestFileDuplicate = 132,
-    ErrorParchiveRepairFailed = 133,
-    ErrorInvalidAccountPassword = 134,
+    Waiting,
+    Downloading,
+    Paused,
+    Finishing,
+    Finished,
+    HashChecking,
+    PreSeeding,
+    Seeding,
+    FilehostingWaiting,
+    Extracting,
+    Preprocessing,
+    PreprocessPass,
+    Downloaded,
+    Postprocessing,
+    CaptchaNeeded,
+    Error,
+    ErrorBrokenLink,
+    ErrorDestNoExist,
+    ErrorDestDeny,
+    ErrorDiskFull,
+    ErrorQuotaReached,
+    ErrorTimeout,
+    ErrorExceedMaxFsSize,
+    ErrorExceedMaxTempFsSize,
+    ErrorExceedMaxDestFsSize,
+    ErrorNameTooLongEncryption,
+    ErrorNameTooLong,
+    ErrorTorrentDuplicate,
+    ErrorFileNoExist,
+    ErrorRequiredPremium,
+    ErrorNotSupportType,
+    ErrorFtpEncryptionNotSupportType,
+    ErrorExtractFail,
+    ErrorExtractWrongPassword,
+    ErrorExtractInvalidArchive,
+    ErrorExtractQuotaReached,
+    ErrorExtractDiskFull,
+    ErrorTorrentInvalid,
+    ErrorRequiredAccount,
+    ErrorTryItLater,
+    ErrorEncryption,
+    ErrorMissingPython,
+    ErrorPrivateVideo,
+    ErrorExtractFolderNotExist,
+    ErrorNzbMissingArticle,
+    ErrorEd2KLinkDuplicate,
+    ErrorDestFileDuplicate,
+    ErrorParchiveRepairFailed,
+    ErrorInvalidAccountPassword,
+    /// Status number not known to this client
+    ///
+    /// The legacy `SYNO.DownloadStation.Task` API reports statuses by name, names not
+    /// known to this client are kept as `Unknown(0)`, a number no status uses, with
+    /// the raw name in [`StatusExtra::status_name`].
+    Unknown(u32),
+}
+
+/// Group of related task statuses, see [`TaskStatus::category`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskStatusCategory {
+    /// Queued, waiting for a file host or for a captcha to be solved
+    Waiting,
+    Downloading,
+    /// Checking, extracting or otherwise processing downloaded data
+    Processing,
+    Seeding,
+    Paused,
+    Finished,
+    Error,
+    /// Status not known to this client, outside of the error range
+    Unknown,
+}
+
+impl TaskStatus {
+    /// Decodes a status number, unknown numbers become [`TaskStatus::Unknown`]
+    #[must_use]
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            1 => TaskStatus::Waiting,
+            2 => TaskStatus::Downloading,
+            3 => TaskStatus::Paused,
+            4 => TaskStatus::Finishing,
+            5 => TaskStatus::Finished,
+            6 => TaskStatus::HashChecking,
+            7 => TaskStatus::PreSeeding,
+            8 => TaskStatus::Seeding,
+            9 => TaskStatus::FilehostingWaiting,
+            10 => TaskStatus::Extracting,
+            11 => TaskStatus::Preprocessing,
+            12 => TaskStatus::PreprocessPass,
+            13 => TaskStatus::Downloaded,
+            14 => TaskStatus::Postprocessing,
+            15 => TaskStatus::CaptchaNeeded,
+            101 => TaskStatus::Error,
+            102 => TaskStatus::ErrorBrokenLink,
+            103 => TaskStatus::ErrorDestNoExist,
+            104 => TaskStatus::ErrorDestDeny,
+            105 => TaskStatus::ErrorDiskFull,
+            106 => TaskStatus::ErrorQuotaReached,
+            107 => TaskStatus::ErrorTimeout,
+            108 => TaskStatus::ErrorExceedMaxFsSize,
+            109 => TaskStatus::ErrorExceedMaxTempFsSize,
+            110 => TaskStatus::ErrorExceedMaxDestFsSize,
+            111 => TaskStatus::ErrorNameTooLongEncryption,
+            112 => TaskStatus::ErrorNameTooLong,
+            113 => TaskStatus::ErrorTorrentDuplicate,
+            114 => TaskStatus::ErrorFileNoExist,
+            115 => TaskStatus::ErrorRequiredPremium,
+            116 => TaskStatus::ErrorNotSupportType,
+            117 => TaskStatus::ErrorFtpEncryptionNotSupportType,
+            118 => TaskStatus::ErrorExtractFail,
+            119 => TaskStatus::ErrorExtractWrongPassword,
+            120 => TaskStatus::ErrorExtractInvalidArchive,
+            121 => TaskStatus::ErrorExtractQuotaReached,
+            122 => TaskStatus::ErrorExtractDiskFull,
+            123 => TaskStatus::ErrorTorrentInvalid,
+            124 => TaskStatus::ErrorRequiredAccount,
+            125 => TaskStatus::ErrorTryItLater,
+            126 => TaskStatus::ErrorEncryption,
+            127 => TaskStatus::ErrorMissingPython,
+            128 => TaskStatus::ErrorPrivateVideo,
+            129 => TaskStatus::ErrorExtractFolderNotExist,
+            130 => TaskStatus::ErrorNzbMissingArticle,
+            131 => TaskStatus::ErrorEd2KLinkDuplicate,
+            132 => TaskStatus::ErrorDestFileDuplicate,
+            133 => TaskStatus::ErrorParchiveRepairFailed,
+            134 => TaskStatus::ErrorInvalidAccountPassword,
+            code => TaskStatus::Unknown(code),
+        }
+    }
+
+    /// Status number used by the API
+    #[must_use]
+    pub fn code(self) -> u32 {
+        match self {
+            TaskStatus::Waiting => 1,
+            TaskStatus::Downloading => 2,
+            TaskStatus::Paused => 3,
+            TaskStatus::Finishing => 4,
+            TaskStatus::Finished => 5,
+            TaskStatus::HashChecking => 6,
+            TaskStatus::PreSeeding => 7,
+            TaskStatus::Seeding => 8,
+            TaskStatus::FilehostingWaiting => 9,
+            TaskStatus::Extracting => 10,
+            TaskStatus::Preprocessing => 11,
+            TaskStatus::PreprocessPass => 12,
+            TaskStatus::Downloaded => 13,
+            TaskStatus::Postprocessing => 14,
+            TaskStatus::CaptchaNeeded => 15,
+            TaskStatus::Error => 101,
+            TaskStatus::ErrorBrokenLink => 102,
+            TaskStatus::ErrorDestNoExist => 103,
+            TaskStatus::ErrorDestDeny => 104,
+            TaskStatus::ErrorDiskFull => 105,
+            TaskStatus::ErrorQuotaReached => 106,
+            TaskStatus::ErrorTimeout => 107,
+            TaskStatus::ErrorExceedMaxFsSize => 108,
+            TaskStatus::ErrorExceedMaxTempFsSize => 109,
+            TaskStatus::ErrorExceedMaxDestFsSize => 110,
+            TaskStatus::ErrorNameTooLongEncryption => 111,
+            TaskStatus::ErrorNameTooLong => 112,
+            TaskStatus::ErrorTorrentDuplicate => 113,
+            TaskStatus::ErrorFileNoExist => 114,
+            TaskStatus::ErrorRequiredPremium => 115,
+            TaskStatus::ErrorNotSupportType => 116,
+            TaskStatus::ErrorFtpEncryptionNotSupportType => 117,
+            TaskStatus::ErrorExtractFail => 118,
+            TaskStatus::ErrorExtractWrongPassword => 119,
+            TaskStatus::ErrorExtractInvalidArchive => 120,
+            TaskStatus::ErrorExtractQuotaReached => 121,
+            TaskStatus::ErrorExtractDiskFull => 122,
+            TaskStatus::ErrorTorrentInvalid => 123,
+            TaskStatus::ErrorRequiredAccount => 124,
+            TaskStatus::ErrorTryItLater => 125,
+            TaskStatus::ErrorEncryption => 126,
+            TaskStatus::ErrorMissingPython => 127,
+            TaskStatus::ErrorPrivateVideo => 128,
+            TaskStatus::ErrorExtractFolderNotExist => 129,
+            TaskStatus::ErrorNzbMissingArticle => 130,
+            TaskStatus::ErrorEd2KLinkDuplicate => 131,
+            TaskStatus::ErrorDestFileDuplicate => 132,
+            TaskStatus::ErrorParchiveRepairFailed => 133,
+            TaskStatus::ErrorInvalidAccountPassword => 134,
+            TaskStatus::Unknown(code) => code,
+        }
+    }
+
+    /// Human-readable description of the status
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            TaskStatus::Waiting => "Waiting",
+            TaskStatus::Downloading => "Downloading",
+            TaskStatus::Paused => "Paused",
+            TaskStatus::Finishing => "Finishing",
+            TaskStatus::Finished => "Finished",
+            TaskStatus::HashChecking => "Checking hash",
+            TaskStatus::PreSeeding => "Preparing to seed",
+            TaskStatus::Seeding => "Seeding",
+            TaskStatus::FilehostingWaiting => "Waiting for file hosting",
+            TaskStatus::Extracting => "Extracting",
+            TaskStatus::Preprocessing => "Preprocessing",
+            TaskStatus::PreprocessPass => "Preprocessing passed",
+            TaskStatus::Downloaded => "Downloaded",
+            TaskStatus::Postprocessing => "Postprocessing",
+            TaskStatus::CaptchaNeeded => "Captcha needed",
+            TaskStatus::Error => "Error",
+            TaskStatus::ErrorBrokenLink => "Broken link",
+            TaskStatus::ErrorDestNoExist => "Destination does not exist",
+            TaskStatus::ErrorDestDeny => "Destination denied",
+            TaskStatus::ErrorDiskFull => "Disk full",
+            TaskStatus::ErrorQuotaReached => "Quota reached",
+            TaskStatus::ErrorTimeout => "Timed out",
+            TaskStatus::ErrorExceedMaxFsSize => "File exceeds the maximum file system size",
+            TaskStatus::ErrorExceedMaxTempFsSize => {
+                "File exceeds the maximum temporary file system size"
+            }
+            TaskStatus::ErrorExceedMaxDestFsSize => {
+                "File exceeds the maximum destination file system size"
+            }
+            TaskStatus::ErrorNameTooLongEncryption => "File name too long for an encrypted folder",
+            TaskStatus::ErrorNameTooLong => "File name too long",
+            TaskStatus::ErrorTorrentDuplicate => "Duplicate torrent",
+            TaskStatus::ErrorFileNoExist => "File does not exist",
+            TaskStatus::ErrorRequiredPremium => "Premium account required",
+            TaskStatus::ErrorNotSupportType => "Unsupported file type",
+            TaskStatus::ErrorFtpEncryptionNotSupportType => "FTP encryption type not supported",
+            TaskStatus::ErrorExtractFail => "Extraction failed",
+            TaskStatus::ErrorExtractWrongPassword => "Extraction failed, wrong password",
+            TaskStatus::ErrorExtractInvalidArchive => "Extraction failed, invalid archive",
+            TaskStatus::ErrorExtractQuotaReached => "Extraction failed, quota reached",
+            TaskStatus::ErrorExtractDiskFull => "Extraction failed, disk full",
+            TaskStatus::ErrorTorrentInvalid => "Invalid torrent",
+            TaskStatus::ErrorRequiredAccount => "Account required",
+            TaskStatus::ErrorTryItLater => "Try again later",
+            TaskStatus::ErrorEncryption => "Encryption error",
+            TaskStatus::ErrorMissingPython => "Python is missing",
+            TaskStatus::ErrorPrivateVideo => "Private video",
+            TaskStatus::ErrorExtractFolderNotExist => "Extraction folder does not exist",
+            TaskStatus::ErrorNzbMissingArticle => "NZB article missing",
+            TaskStatus::ErrorEd2KLinkDuplicate => "Duplicate ed2k link",
+            TaskStatus::ErrorDestFileDuplicate => "Destination file already exists",
+            TaskStatus::ErrorParchiveRepairFailed => "Parchive repair failed",
+            TaskStatus::ErrorInvalidAccountPassword => "Invalid account or password",
+            TaskStatus::Unknown(_) => "Unknown status",
+        }
+    }
+
+    /// Group the status belongs to. Unknown statuses from 101 up count as errors.
+    #[must_use]
+    pub fn category(self) -> TaskStatusCategory {
+        match self {
+            TaskStatus::Waiting | TaskStatus::FilehostingWaiting | TaskStatus::CaptchaNeeded => {
+                TaskStatusCategory::Waiting
+            }
+            TaskStatus::Downloading => TaskStatusCategory::Downloading,
+            TaskStatus::Finishing
+            | TaskStatus::HashChecking
+            | TaskStatus::PreSeeding
+            | TaskStatus::Extracting
+            | TaskStatus::Preprocessing
+            | TaskStatus::PreprocessPass
+            | TaskStatus::Downloaded
+            | TaskStatus::Postprocessing => TaskStatusCategory::Processing,
+            TaskStatus::Seeding => TaskStatusCategory::Seeding,
+            TaskStatus::Paused => TaskStatusCategory::Paused,
+            TaskStatus::Finished => TaskStatusCategory::Finished,
+            status if status.code() >= TaskStatus::Error.code() => TaskStatusCategory::Error,
+            _ => TaskStatusCategory::Unknown,
+        }
+    }
+
+    /// Whether the task failed, see [`TaskStatusCategory::Error`]
+    #[must_use]
+    pub fn is_error(self) -> bool {
+        self.category() == TaskStatusCategory::Error
+    }
+
+    /// Whether the task is downloading, processing or seeding
+    #[must_use]
+    pub fn is_active(self) -> bool {
+        matches!(
+            self.category(),
+            TaskStatusCategory::Downloading
+                | TaskStatusCategory::Processing
+                | TaskStatusCategory::Seeding
+        )
+    }
+
+    /// Whether the task has finished downloading, it is finished or seeding
+    #[must_use]
+    pub fn is_completed(self) -> bool {
+        matches!(self, TaskStatus::Finished | TaskStatus::Seeding)
+    }
+
+    /// Whether the task won't change anymore on its own, it is finished or failed
+    #[must_use]
+    pub fn is_terminal(self) -> bool {
+        self == TaskStatus::Finished || self.is_error()
+    }
+
+    /// Whether the task was paused
+    #[must_use]
+    pub fn is_paused(self) -> bool {
+        self == TaskStatus::Paused
+    }
+
+    /// Whether the task can be resumed, it is paused or failed
+    #[must_use]
+    pub fn is_resumable(self) -> bool {
+        self.is_paused() || self.is_error()
+    }
+}
+
+impl From<u32> for TaskStatus {
+    fn from(code: u32) -> Self {
+        TaskStatus::from_code(code)
+    }
+}
+
+impl From<TaskStatus> for u32 {
+    fn from(status: TaskStatus) -> Self {
+        status.code()
+    }
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskStatus::Unknown(code) => write!(f, "{} ({code})", self.description()),
+            _ => f.write_str(self.description()),
+        }
+    }
+}
+
+impl Serialize for TaskStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u32::deserialize(deserializer).map(TaskStatus::from_code)
+    }
 }
 
 /// Error information from Synology API
@@ -210,14 +763,50 @@ pub struct TaskCompleted {
     pub task_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+/// Result of creating a task
+///
+/// When the task is created with a file list, only `list_id` is set until the list is
+/// confirmed with [`crate::client::SynoDS::confirm_task_list`].
+#[derive(Deserialize, Debug, Default)]
 pub struct TaskCreated {
+    /// IDs of the file lists waiting for confirmation
+    #[serde(default, deserialize_with = "one_or_many")]
     pub list_id: Vec<String>,
+    /// IDs of the created tasks
+    #[serde(default, deserialize_with = "one_or_many")]
     pub task_id: Vec<String>,
 }
 
+/// File list of a task created with `create_list`, see [`crate::client::SynoDS::get_task_list`]
+#[derive(Deserialize, Debug)]
+pub struct TaskList {
+    /// Title of the task to be created
+    #[serde(default)]
+    pub title: String,
+    /// Total size in bytes
+    #[serde(default)]
+    pub size: u64,
+    /// Type of download task (e.g., "bt" for `BitTorrent`)
+    #[serde(default, rename = "type")]
+    pub task_type: String,
+    /// Files the task can download
+    #[serde(default)]
+    pub files: Vec<TaskListFile>,
+}
+
+/// File in a [`TaskList`]
+#[derive(Deserialize, Debug)]
+pub struct TaskListFile {
+    /// Index used to select the file when confirming the list
+    pub index: u32,
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct TaskOperation {
+    #[serde(default)]
     pub failed_task: Vec<FailedTask>,
 }
 
@@ -226,3 +815,386 @@ pub struct FailedTask {
     pub error: i32,
     pub id: String,
 }
+
+impl FailedTask {
+    /// Decodes the error code of the failed task with the codes of the task APIs
+    #[must_use]
+    pub fn error_code(&self) -> ErrorCode {
+        ErrorCode::for_api("SYNO.DownloadStation2.Task", self.error)
+    }
+}
+
+/// Current transfer speeds of all tasks, as measured by Download Station
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferStatistics {
+    /// Total download speed in bytes per second
+    #[serde(alias = "download_rate", default)]
+    pub speed_download: u64,
+    /// Total upload speed in bytes per second
+    #[serde(alias = "upload_rate", default)]
+    pub speed_upload: u64,
+    /// eMule download speed in bytes per second, `None` if eMule is disabled
+    #[serde(alias = "emule_download_rate", default)]
+    pub emule_speed_download: Option<u64>,
+    /// eMule upload speed in bytes per second, `None` if eMule is disabled
+    #[serde(alias = "emule_upload_rate", default)]
+    pub emule_speed_upload: Option<u64>,
+}
+
+/// Totals over a list of tasks, calculated locally with [`Tasks::summary`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TasksSummary {
+    /// Number of tasks in each status, statuses without tasks are left out
+    pub status_counts: HashMap<TaskStatus, usize>,
+    /// Sum of the task sizes in bytes
+    pub total_size: u64,
+    /// Bytes left to download, over the tasks with transfer information
+    pub remaining: u64,
+    /// Mean upload ratio of the tasks with transfer information, `None` if there are none
+    pub mean_ratio: Option<f64>,
+}
+
+impl TasksSummary {
+    /// Number of tasks in the given status
+    #[must_use]
+    pub fn count(&self, status: TaskStatus) -> usize {
+        self.status_counts.get(&status).copied().unwrap_or_default()
+    }
+}
+
+/// Outcome of an operation on several tasks, listing which tasks succeeded and which failed
+#[derive(Debug, Default)]
+pub struct BatchResult {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<TaskFailure>,
+}
+
+/// Task for which an operation failed
+#[derive(Debug)]
+pub struct TaskFailure {
+    pub id: String,
+    /// Raw Synology error code
+    pub code: i32,
+    /// Decoded error code
+    pub error: ErrorCode,
+}
+
+impl BatchResult {
+    /// Builds the result of an operation on `ids`, of which the `failed_tasks` failed
+    pub(crate) fn new(ids: &[&str], failed_tasks: Vec<FailedTask>) -> Self {
+        let succeeded = ids
+            .iter()
+            .filter(|id| !failed_tasks.iter().any(|failed| failed.id == **id))
+            .map(|id| (*id).to_string())
+            .collect();
+        let failed = failed_tasks
+            .into_iter()
+            .map(|failed| TaskFailure {
+                error: failed.error_code(),
+                code: failed.error,
+                id: failed.id,
+            })
+            .collect();
+
+        Self { succeeded, failed }
+    }
+
+    /// Whether the operation succeeded for all tasks
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Collection of RSS feeds
+#[derive(Deserialize, Debug)]
+pub struct RssSites {
+    #[serde(default)]
+    pub offset: u32,
+    #[serde(default)]
+    pub site: Vec<RssSite>,
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// RSS feed subscribed to in Download Station
+#[derive(Deserialize, Debug, Clone)]
+pub struct RssSite {
+    /// Unique identifier for the feed
+    pub id: u32,
+    pub title: String,
+    /// URL of the feed
+    pub url: String,
+    /// Whether the feed is being refreshed right now
+    #[serde(default)]
+    pub is_updating: bool,
+    /// When the feed was last refreshed
+    #[serde(with = "ts_seconds")]
+    pub last_update: DateTime<Utc>,
+}
+
+/// Collection of items of an RSS feed
+#[derive(Deserialize, Debug)]
+pub struct RssItems {
+    #[serde(default)]
+    pub offset: u32,
+    #[serde(default)]
+    pub item: Vec<RssItem>,
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// Item of an RSS feed
+#[derive(Deserialize, Debug, Clone)]
+pub struct RssItem {
+    /// Unique identifier for the item
+    pub id: String,
+    pub title: String,
+    /// URL or magnet link the item is downloaded from
+    pub download_uri: String,
+    /// Link to the item's web page
+    #[serde(default)]
+    pub external_link: String,
+    /// Size in bytes, 0 if the feed doesn't report it
+    #[serde(default)]
+    pub size: u64,
+    /// When the item was published
+    #[serde(with = "ts_seconds")]
+    pub date: DateTime<Utc>,
+    /// Whether the item appeared since the feed was last viewed
+    #[serde(default)]
+    pub is_new: bool,
+}
+
+/// Collection of RSS auto-download filters
+#[derive(Deserialize, Debug)]
+pub struct RssFilters {
+    #[serde(default)]
+    pub offset: u32,
+    #[serde(default)]
+    pub filter: Vec<RssFilter>,
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// Filter automatically downloading new items of an RSS feed
+///
+/// Patterns are lists of keywords separated by whitespace, matched case-insensitively
+/// against item titles: an item matches if its title contains all keywords of
+/// `match_pattern` and none of `not_match_pattern`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RssFilter {
+    /// Unique identifier for the filter, ignored when adding and required when updating a filter
+    #[serde(default)]
+    pub id: u32,
+    /// ID of the feed the filter applies to
+    pub rss_id: u32,
+    pub name: String,
+    /// Keywords an item title must contain
+    #[serde(default, rename = "match")]
+    pub match_pattern: String,
+    /// Keywords an item title must not contain
+    #[serde(default, rename = "not_match")]
+    pub not_match_pattern: String,
+    /// Destination of the created tasks
+    pub destination: String,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+impl RssFilter {
+    /// Whether the filter would download the item, regardless of `enabled`
+    #[must_use]
+    pub fn matches(&self, item: &RssItem) -> bool {
+        let title = item.title.to_lowercase();
+        let contains = |keyword: &str| title.contains(&keyword.to_lowercase());
+
+        self.match_pattern.split_whitespace().all(contains)
+            && !self.not_match_pattern.split_whitespace().any(contains)
+    }
+}
+
+/// Snapshot of the results of a `BitTorrent` search
+#[derive(Deserialize, Debug)]
+pub struct BtSearchResults {
+    /// Whether all search modules are done
+    #[serde(default)]
+    pub finished: bool,
+    #[serde(default)]
+    pub items: Vec<BtSearchResult>,
+    #[serde(default)]
+    pub total: u32,
+}
+
+/// Torrent found by a `BitTorrent` search
+#[derive(Deserialize, Debug, Clone)]
+pub struct BtSearchResult {
+    pub title: String,
+    /// Size in bytes
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub seeds: u32,
+    #[serde(default)]
+    pub peers: u32,
+    /// URL or magnet link the torrent is downloaded from
+    pub download_uri: String,
+    /// Link to the torrent's web page
+    #[serde(default)]
+    pub external_link: String,
+    /// ID of the search module that found the torrent
+    #[serde(default)]
+    pub module_id: String,
+    /// Name of the search module that found the torrent
+    #[serde(default)]
+    pub module_title: String,
+}
+
+/// Category that `BitTorrent` searches can be limited to
+#[derive(Deserialize, Debug, Clone)]
+pub struct BtSearchCategory {
+    pub id: String,
+    pub title: String,
+}
+
+/// Site searched by `BitTorrent` searches
+#[derive(Deserialize, Debug, Clone)]
+pub struct BtSearchModule {
+    pub id: String,
+    pub title: String,
+    /// Whether the module is used for searches
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Download Station package information
+#[derive(Deserialize, Debug, Clone)]
+pub struct PackageInfo {
+    /// Build number of the installed package
+    #[serde(default)]
+    pub version: u32,
+    /// Full version of the installed package, e.g. `4.0.1-4680`
+    #[serde(default)]
+    pub version_string: String,
+    /// Whether the logged in user can manage all tasks and the settings
+    #[serde(default)]
+    pub is_manager: bool,
+    /// Folder new tasks are downloaded to when no destination is given, empty if not set
+    #[serde(default)]
+    pub default_destination: String,
+}
+
+/// Running state of the Download Station package service
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceStatus {
+    Running,
+    #[serde(alias = "stop")]
+    Stopped,
+    /// State not known to this client, e.g. a broken or upgrading package
+    #[serde(other)]
+    Unknown,
+}
+
+impl ServiceStatus {
+    /// Whether the service is running
+    #[must_use]
+    pub fn is_running(self) -> bool {
+        self == ServiceStatus::Running
+    }
+}
+
+/// Deserializes a value the API returns either as a single string or as a list of strings
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_status_codes() {
+        assert_eq!(TaskStatus::from_code(2), TaskStatus::Downloading);
+        assert_eq!(
+            TaskStatus::from_code(134),
+            TaskStatus::ErrorInvalidAccountPassword
+        );
+        assert_eq!(TaskStatus::from_code(42), TaskStatus::Unknown(42));
+        assert_eq!(TaskStatus::Unknown(42).code(), 42);
+        assert_eq!(u32::from(TaskStatus::ErrorDiskFull), 105);
+        for code in 0..=1000 {
+            assert_eq!(TaskStatus::from_code(code).code(), code);
+        }
+    }
+
+    #[test]
+    fn test_unknown_task_status_is_deserialized() {
+        let task: Task = serde_json::from_str(
+            r#"{"id":"task_id_1","username":"test","type":"bt","title":"Test","size":1,"status":200}"#,
+        )
+        .unwrap();
+        assert_eq!(task.status, TaskStatus::Unknown(200));
+        assert_eq!(serde_json::to_string(&task.status).unwrap(), "200");
+
+        let task: Task = serde_json::from_str(
+            r#"{"id":"task_id_1","username":"test","type":"bt","title":"Test","size":1,"status":1001}"#,
+        )
+        .unwrap();
+        assert_eq!(task.status, TaskStatus::Unknown(1001));
+    }
+
+    #[test]
+    fn test_task_status_classification() {
+        assert_eq!(
+            TaskStatus::Extracting.category(),
+            TaskStatusCategory::Processing
+        );
+        assert_eq!(
+            TaskStatus::Unknown(16).category(),
+            TaskStatusCategory::Unknown
+        );
+        assert_eq!(
+            TaskStatus::Unknown(200).category(),
+            TaskStatusCategory::Error
+        );
+
+        assert!(TaskStatus::ErrorBrokenLink.is_error());
+        assert!(TaskStatus::ErrorBrokenLink.is_terminal());
+        assert!(TaskStatus::ErrorBrokenLink.is_resumable());
+        assert!(!TaskStatus::Seeding.is_error());
+
+        assert!(TaskStatus::Seeding.is_active());
+        assert!(TaskStatus::Seeding.is_completed());
+        assert!(!TaskStatus::Seeding.is_terminal());
+        assert!(!TaskStatus::Waiting.is_active());
+
+        assert!(TaskStatus::Paused.is_paused());
+        assert!(TaskStatus::Paused.is_resumable());
+        assert!(!TaskStatus::Finished.is_resumable());
+        assert!(TaskStatus::Finished.is_terminal());
+    }
+
+    #[test]
+    fn test_task_status_display() {
+        assert_eq!(TaskStatus::HashChecking.to_string(), "Checking hash");
+        assert_eq!(
+            TaskStatus::ErrorDestNoExist.to_string(),
+            "Destination does not exist"
+        );
+        assert_eq!(TaskStatus::Unknown(42).to_string(), "Unknown status (42)");
+    }
+}
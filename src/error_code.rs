@@ -0,0 +1,316 @@
+use std::fmt;
+
+/// Error codes returned by the Synology Web API
+///
+/// Codes 100-119 are shared by all APIs. Codes from 400 up are API specific: the same
+/// number means different things for `SYNO.API.Auth` and for the Download Station task
+/// APIs, so they are decoded with [`ErrorCode::auth`] or [`ErrorCode::task`] respectively,
+/// or with [`ErrorCode::for_api`] from the name of the API that returned them.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// 100
+    Unknown,
+    /// 101
+    InvalidParameter,
+    /// 102
+    ApiNotFound,
+    /// 103
+    MethodNotFound,
+    /// 104
+    VersionNotSupported,
+    /// 105
+    PermissionDenied,
+    /// 106
+    SessionTimeout,
+    /// 107
+    SessionInterrupted,
+    /// 108
+    FileUploadFailed,
+    /// 109, 110, 111, 117, 118
+    SystemBusy(i32),
+    /// 114
+    MissingParameter,
+    /// 115
+    FileUploadNotAllowed,
+    /// 116
+    DemoSiteNotAllowed,
+    /// 119
+    InvalidSession,
+
+    /// 400 from `SYNO.API.Auth`
+    IncorrectCredentials,
+    /// 401 from `SYNO.API.Auth`
+    AccountDisabled,
+    /// 402 from `SYNO.API.Auth`
+    AuthPermissionDenied,
+    /// 403 from `SYNO.API.Auth`
+    OtpRequired,
+    /// 404 from `SYNO.API.Auth`
+    OtpInvalid,
+    /// 406 from `SYNO.API.Auth`
+    OtpEnforced,
+    /// 407 from `SYNO.API.Auth`
+    IpBlocked,
+    /// 408 from `SYNO.API.Auth`
+    PasswordExpiredCannotChange,
+    /// 409 from `SYNO.API.Auth`
+    PasswordExpired,
+    /// 410 from `SYNO.API.Auth`
+    PasswordMustChange,
+
+    /// 400 from the Download Station task APIs
+    TaskFileUploadFailed,
+    /// 401 from the Download Station task APIs
+    MaxTasksReached,
+    /// 402 from the Download Station task APIs
+    DestinationDenied,
+    /// 403 from the Download Station task APIs
+    DestinationNotFound,
+    /// 404 from the Download Station task APIs
+    InvalidTaskId,
+    /// 405 from the Download Station task APIs
+    InvalidTaskAction,
+    /// 406 from the Download Station task APIs
+    NoDefaultDestination,
+    /// 407 from the Download Station task APIs
+    SetDestinationFailed,
+    /// 408 from the Download Station task APIs
+    FileNotFound,
+
+    /// Any code not listed above
+    Other(i32),
+}
+
+impl ErrorCode {
+    /// Decodes an error code returned by the given API, using the codes of its family
+    ///
+    /// Codes from APIs outside of `SYNO.API.Auth` and the Download Station task APIs
+    /// (`SYNO.DownloadStation.Task`, `SYNO.DownloadStation2.Task` and its sub-APIs), such
+    /// as `SYNO.API.Info`, `SYNO.DownloadStation2.RSS.Site` or `SYNO.Core.Package`, are only
+    /// decoded when shared by all APIs.
+    #[must_use]
+    pub fn for_api(api: &str, code: i32) -> Self {
+        if api == "SYNO.API.Auth" {
+            Self::auth(code)
+        } else if is_task_api(api) {
+            Self::task(code)
+        } else {
+            Self::common(code)
+        }
+    }
+
+    /// Decodes an error code returned by `SYNO.API.Auth`
+    #[must_use]
+    pub fn auth(code: i32) -> Self {
+        match code {
+            400 => Self::IncorrectCredentials,
+            401 => Self::AccountDisabled,
+            402 => Self::AuthPermissionDenied,
+            403 => Self::OtpRequired,
+            404 => Self::OtpInvalid,
+            406 => Self::OtpEnforced,
+            407 => Self::IpBlocked,
+            408 => Self::PasswordExpiredCannotChange,
+            409 => Self::PasswordExpired,
+            410 => Self::PasswordMustChange,
+            code => Self::common(code),
+        }
+    }
+
+    /// Decodes an error code returned by the Download Station task APIs
+    ///
+    /// Only the documented codes 400-408 are decoded. The NAS may report other
+    /// conditions, such as a duplicate task or a reached quota, with undocumented codes,
+    /// which are kept as [`ErrorCode::Other`].
+    #[must_use]
+    pub fn task(code: i32) -> Self {
+        match code {
+            400 => Self::TaskFileUploadFailed,
+            401 => Self::MaxTasksReached,
+            402 => Self::DestinationDenied,
+            403 => Self::DestinationNotFound,
+            404 => Self::InvalidTaskId,
+            405 => Self::InvalidTaskAction,
+            406 => Self::NoDefaultDestination,
+            407 => Self::SetDestinationFailed,
+            408 => Self::FileNotFound,
+            code => Self::common(code),
+        }
+    }
+
+    /// Decodes an error code shared by all APIs
+    fn common(code: i32) -> Self {
+        match code {
+            100 => Self::Unknown,
+            101 => Self::InvalidParameter,
+            102 => Self::ApiNotFound,
+            103 => Self::MethodNotFound,
+            104 => Self::VersionNotSupported,
+            105 => Self::PermissionDenied,
+            106 => Self::SessionTimeout,
+            107 => Self::SessionInterrupted,
+            108 => Self::FileUploadFailed,
+            109..=111 | 117 | 118 => Self::SystemBusy(code),
+            114 => Self::MissingParameter,
+            115 => Self::FileUploadNotAllowed,
+            116 => Self::DemoSiteNotAllowed,
+            119 => Self::InvalidSession,
+            code => Self::Other(code),
+        }
+    }
+
+    /// Human-readable description of the error code
+    #[must_use]
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown error",
+            Self::InvalidParameter => "Invalid parameter",
+            Self::ApiNotFound => "The requested API does not exist",
+            Self::MethodNotFound => "The requested method does not exist",
+            Self::VersionNotSupported => "The requested version does not support the functionality",
+            Self::PermissionDenied => "The logged in session does not have permission",
+            Self::SessionTimeout => "Session timeout",
+            Self::SessionInterrupted => "Session interrupted by duplicate login",
+            Self::FileUploadFailed => "Failed to upload the file",
+            Self::SystemBusy(_) => "The network connection is unstable or the system is busy",
+            Self::MissingParameter => "Lost parameters for this API",
+            Self::FileUploadNotAllowed => "Not allowed to upload a file",
+            Self::DemoSiteNotAllowed => "Not allowed to perform for a demo site",
+            Self::InvalidSession => "Invalid session",
+            Self::IncorrectCredentials => "No such account or incorrect password",
+            Self::AccountDisabled => "Disabled account",
+            Self::AuthPermissionDenied => "Denied permission",
+            Self::OtpRequired => "2-factor authentication code required",
+            Self::OtpInvalid => "Failed to authenticate 2-factor authentication code",
+            Self::OtpEnforced => "Enforce to authenticate with 2-factor authentication code",
+            Self::IpBlocked => "Blocked IP source",
+            Self::PasswordExpiredCannotChange => "Expired password cannot change",
+            Self::PasswordExpired => "Expired password",
+            Self::PasswordMustChange => "Password must be changed",
+            Self::TaskFileUploadFailed => "File upload failed",
+            Self::MaxTasksReached => "Max number of tasks reached",
+            Self::DestinationDenied => "Destination denied",
+            Self::DestinationNotFound => "Destination does not exist",
+            Self::InvalidTaskId => "Invalid task id",
+            Self::InvalidTaskAction => "Invalid task action",
+            Self::NoDefaultDestination => "No default destination",
+            Self::SetDestinationFailed => "Set destination failed",
+            Self::FileNotFound => "File does not exist",
+            Self::Other(_) => "Unrecognized error code",
+        }
+    }
+
+    /// Whether repeating the request (after re-authorizing, for session errors)
+    /// may succeed
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::SessionTimeout
+                | Self::SessionInterrupted
+                | Self::SystemBusy(_)
+                | Self::InvalidSession
+        )
+    }
+
+    /// Whether the error is caused by missing permissions
+    #[must_use]
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(
+            self,
+            Self::PermissionDenied
+                | Self::FileUploadNotAllowed
+                | Self::DemoSiteNotAllowed
+                | Self::AuthPermissionDenied
+                | Self::DestinationDenied
+        )
+    }
+}
+
+/// Whether `api` is one of the Download Station task APIs, such as
+/// `SYNO.DownloadStation.Task` or `SYNO.DownloadStation2.Task.BT.File`
+fn is_task_api(api: &str) -> bool {
+    let mut parts = api.split('.');
+    parts.next() == Some("SYNO")
+        && parts
+            .next()
+            .is_some_and(|family| family.starts_with("DownloadStation"))
+        && parts.next() == Some("Task")
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_decoded_per_api() {
+        assert_eq!(ErrorCode::auth(403), ErrorCode::OtpRequired);
+        assert_eq!(ErrorCode::task(403), ErrorCode::DestinationNotFound);
+        assert_eq!(ErrorCode::auth(105), ErrorCode::PermissionDenied);
+        assert_eq!(ErrorCode::task(105), ErrorCode::PermissionDenied);
+        assert_eq!(ErrorCode::task(110), ErrorCode::SystemBusy(110));
+        assert_eq!(ErrorCode::task(999), ErrorCode::Other(999));
+        assert_eq!(ErrorCode::task(409), ErrorCode::Other(409));
+    }
+
+    #[test]
+    fn test_codes_are_decoded_per_api_family() {
+        assert_eq!(
+            ErrorCode::for_api("SYNO.API.Auth", 403),
+            ErrorCode::OtpRequired
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.DownloadStation2.Task", 403),
+            ErrorCode::DestinationNotFound
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.DownloadStation.Task", 405),
+            ErrorCode::InvalidTaskAction
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.DownloadStation2.Task.BT.File", 404),
+            ErrorCode::InvalidTaskId
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.DownloadStation2.RSS.Site", 403),
+            ErrorCode::Other(403)
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.DownloadStation2.BTSearch", 101),
+            ErrorCode::InvalidParameter
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.Core.Package.Control", 403),
+            ErrorCode::Other(403)
+        );
+        assert_eq!(
+            ErrorCode::for_api("SYNO.API.Info", 105),
+            ErrorCode::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn test_classification() {
+        assert!(ErrorCode::task(119).is_retryable());
+        assert!(ErrorCode::task(117).is_retryable());
+        assert!(!ErrorCode::task(101).is_retryable());
+        assert!(ErrorCode::task(402).is_permission_denied());
+        assert!(!ErrorCode::task(403).is_permission_denied());
+        assert!(ErrorCode::auth(402).is_permission_denied());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            ErrorCode::auth(400).to_string(),
+            "No such account or incorrect password"
+        );
+    }
+}
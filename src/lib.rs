@@ -16,7 +16,13 @@
 //! - Create downloads from torrent files
 //! - Control tasks (pause, resume, complete)
 //! - Clear completed downloads
-//! - Human-readable file sizes, progress and ratio calculation and ETA
+//! - Read and change the global Download Station settings
+//! - Manage RSS feeds and download their items
+//! - Search torrents with the built-in torrent search
+//! - Check, start and stop the Download Station service
+//! - Watch tasks for changes as a stream of events
+//! - Wait for a download to complete
+//! - Numeric and formatted sizes, speeds, progress, ratio and ETA
 //!
 //! ## Usage example
 //!
@@ -56,4 +62,7 @@
 
 pub mod client;
 pub mod entities;
+pub mod error_code;
+pub mod session;
+pub mod settings;
 pub mod utils;
@@ -0,0 +1,136 @@
+use crate::client::SynoError::Configuration;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{ErrorKind, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::PathBuf;
+
+/// Session obtained from the NAS, which can be saved and restored to avoid logging in
+/// again on every process start
+///
+/// The session ID and token are redacted from the `Debug` output.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Session {
+    /// Session ID used for authenticated requests
+    pub sid: String,
+    #[serde(default)]
+    pub synotoken: String,
+    /// Device ID of a trusted device, if 2-factor authentication is used
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// When the session was obtained
+    pub obtained_at: DateTime<Utc>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("sid", &"***")
+            .field("synotoken", &"***")
+            .field("device_id", &self.device_id)
+            .field("obtained_at", &self.obtained_at)
+            .finish()
+    }
+}
+
+/// Storage for a [`Session`], used by [`crate::client::SynoDS`] to restore the session
+/// on start, save it after every login and clear it on logout
+pub trait SessionStore: Send + Sync {
+    /// Loads the stored session, returns `None` if there is none
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored session cannot be read
+    fn load(&self) -> Result<Option<Session>>;
+
+    /// Stores the session, replacing any previously stored one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be written
+    fn save(&self, session: &Session) -> Result<()>;
+
+    /// Removes the stored session
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored session cannot be removed
+    fn clear(&self) -> Result<()>;
+}
+
+/// [`SessionStore`] keeping the session as a JSON file
+#[derive(Debug, Clone)]
+pub struct FileSessionStore {
+    path: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Creates a store keeping the session in the file at the given path
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is empty
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if path.as_os_str().is_empty() {
+            return Err(Configuration("Session file path cannot be empty".into()).into());
+        }
+
+        Ok(Self { path })
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self) -> Result<Option<Session>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!("Failed to read session file {}", self.path.display())
+                });
+            }
+        };
+
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse session file {}", self.path.display()))
+    }
+
+    fn save(&self, session: &Session) -> Result<()> {
+        let contents = serde_json::to_string(session).context("Failed to serialize session")?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // The session ID grants access to the NAS, keep it readable by the owner only
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options
+            .open(&self.path)
+            .with_context(|| format!("Failed to open session file {}", self.path.display()))?;
+        // The mode only applies to new files, restrict an existing file as well
+        #[cfg(unix)]
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .with_context(|| {
+                format!(
+                    "Failed to restrict permissions of session file {}",
+                    self.path.display()
+                )
+            })?;
+        file.write_all(contents.as_bytes())
+            .with_context(|| format!("Failed to write session file {}", self.path.display()))
+    }
+
+    fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error)
+                .with_context(|| format!("Failed to remove session file {}", self.path.display())),
+            _ => Ok(()),
+        }
+    }
+}
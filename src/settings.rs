@@ -0,0 +1,545 @@
+//! Typed sections of the global Download Station settings (`SYNO.DownloadStation2.Settings.*`)
+//!
+//! Every section is read as a whole with [`crate::client::SynoDS::get_settings`] and
+//! changed with a partial update through [`crate::client::SynoDS::update_settings`]:
+//! only the fields set in the update are sent to the NAS.
+//!
+//! ```rust,no_run
+//! # use syno_download_station::client::SynoDS;
+//! # use syno_download_station::settings::{BtSettings, BtSettingsUpdate};
+//! # async fn example(synods: &SynoDS) -> anyhow::Result<()> {
+//! let bt = synods.get_settings::<BtSettings>().await?;
+//! if bt.max_peers < 200 {
+//!     let update = BtSettingsUpdate {
+//!         max_peers: Some(200),
+//!         ..Default::default()
+//!     };
+//!     synods.update_settings(&update).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::SynoError;
+use crate::client::SynoError::{InvalidInput, InvalidResponse};
+use anyhow::Result;
+use chrono::Weekday;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Write;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Section of the Download Station settings
+pub trait Settings: DeserializeOwned {
+    /// Name of the API providing the section
+    const API_NAME: &'static str;
+
+    /// Partial update of the section
+    type Update: SettingsUpdate<Settings = Self>;
+}
+
+/// Partial update of a [`Settings`] section, fields left `None` are not changed
+pub trait SettingsUpdate: Serialize {
+    /// Section changed by the update
+    type Settings: Settings<Update = Self>;
+}
+
+/// General settings (`SYNO.DownloadStation2.Settings.Global`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct GlobalSettings {
+    /// Shared folder path new tasks are saved to when no destination is given
+    pub default_destination: String,
+    /// Maximum number of tasks downloading at the same time
+    pub max_concurrent_download: u32,
+}
+
+/// Partial update of [`GlobalSettings`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct GlobalSettingsUpdate {
+    pub default_destination: Option<String>,
+    pub max_concurrent_download: Option<u32>,
+}
+
+impl Settings for GlobalSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.Global";
+    type Update = GlobalSettingsUpdate;
+}
+
+impl SettingsUpdate for GlobalSettingsUpdate {
+    type Settings = GlobalSettings;
+}
+
+/// `BitTorrent` settings (`SYNO.DownloadStation2.Settings.BT`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct BtSettings {
+    /// TCP port for incoming connections
+    pub tcp_port: u16,
+    /// Whether to accept encrypted connections only
+    pub enable_encryption: bool,
+    /// Whether the distributed hash table is used to find peers
+    pub enable_dht: bool,
+    /// UDP port of the distributed hash table
+    pub dht_port: u16,
+    /// Maximum number of peers per task
+    pub max_peers: u32,
+    /// Maximum download rate in KB/s, 0 for unlimited
+    pub max_download_rate: u32,
+    /// Maximum upload rate in KB/s, 0 for unlimited
+    pub max_upload_rate: u32,
+    /// Share ratio after which seeding stops, 0 for no limit
+    pub seeding_ratio: f64,
+    /// Minutes after which seeding stops, 0 for no limit
+    pub seeding_interval: u32,
+}
+
+/// Partial update of [`BtSettings`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct BtSettingsUpdate {
+    pub tcp_port: Option<u16>,
+    pub enable_encryption: Option<bool>,
+    pub enable_dht: Option<bool>,
+    pub dht_port: Option<u16>,
+    pub max_peers: Option<u32>,
+    pub max_download_rate: Option<u32>,
+    pub max_upload_rate: Option<u32>,
+    pub seeding_ratio: Option<f64>,
+    pub seeding_interval: Option<u32>,
+}
+
+impl Settings for BtSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.BT";
+    type Update = BtSettingsUpdate;
+}
+
+impl SettingsUpdate for BtSettingsUpdate {
+    type Settings = BtSettings;
+}
+
+/// FTP and HTTP download settings (`SYNO.DownloadStation2.Settings.FtpHttp`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FtpHttpSettings {
+    /// Maximum download rate in KB/s, 0 for unlimited
+    pub max_download_rate: u32,
+    /// Maximum number of connections per download
+    pub max_connections_per_download: u32,
+}
+
+/// Partial update of [`FtpHttpSettings`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FtpHttpSettingsUpdate {
+    pub max_download_rate: Option<u32>,
+    pub max_connections_per_download: Option<u32>,
+}
+
+impl Settings for FtpHttpSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.FtpHttp";
+    type Update = FtpHttpSettingsUpdate;
+}
+
+impl SettingsUpdate for FtpHttpSettingsUpdate {
+    type Settings = FtpHttpSettings;
+}
+
+/// Usenet settings (`SYNO.DownloadStation2.Settings.Nzb`)
+///
+/// The password is never returned by the NAS, it can only be set through
+/// [`NzbSettingsUpdate::password`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct NzbSettings {
+    /// News server host name
+    pub server: String,
+    pub port: u16,
+    pub enable_ssl: bool,
+    /// Whether the news server requires authentication
+    pub enable_auth: bool,
+    pub username: String,
+    /// Maximum number of connections to the news server
+    pub max_connections: u32,
+    /// Maximum download rate in KB/s, 0 for unlimited
+    pub max_download_rate: u32,
+}
+
+/// Partial update of [`NzbSettings`]
+///
+/// The password is redacted from the `Debug` output.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct NzbSettingsUpdate {
+    pub server: Option<String>,
+    pub port: Option<u16>,
+    pub enable_ssl: Option<bool>,
+    pub enable_auth: Option<bool>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub max_connections: Option<u32>,
+    pub max_download_rate: Option<u32>,
+}
+
+impl Settings for NzbSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.Nzb";
+    type Update = NzbSettingsUpdate;
+}
+
+impl SettingsUpdate for NzbSettingsUpdate {
+    type Settings = NzbSettings;
+}
+
+impl fmt::Debug for NzbSettingsUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NzbSettingsUpdate")
+            .field("server", &self.server)
+            .field("port", &self.port)
+            .field("enable_ssl", &self.enable_ssl)
+            .field("enable_auth", &self.enable_auth)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("max_connections", &self.max_connections)
+            .field("max_download_rate", &self.max_download_rate)
+            .finish()
+    }
+}
+
+/// eMule settings (`SYNO.DownloadStation2.Settings.Emule`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct EmuleSettings {
+    /// Whether eMule downloads are enabled
+    pub enable_emule: bool,
+    pub tcp_port: u16,
+    pub udp_port: u16,
+    /// Maximum download rate in KB/s, 0 for unlimited
+    pub max_download_rate: u32,
+    /// Maximum upload rate in KB/s, 0 for unlimited
+    pub max_upload_rate: u32,
+}
+
+/// Partial update of [`EmuleSettings`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct EmuleSettingsUpdate {
+    pub enable_emule: Option<bool>,
+    pub tcp_port: Option<u16>,
+    pub udp_port: Option<u16>,
+    pub max_download_rate: Option<u32>,
+    pub max_upload_rate: Option<u32>,
+}
+
+impl Settings for EmuleSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.Emule";
+    type Update = EmuleSettingsUpdate;
+}
+
+impl SettingsUpdate for EmuleSettingsUpdate {
+    type Settings = EmuleSettings;
+}
+
+/// Watch folder settings (`SYNO.DownloadStation2.Settings.Location`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct LocationSettings {
+    /// Whether torrent and NZB files in the watch folder are added automatically
+    pub enable_watch_folder: bool,
+    /// Shared folder path that is watched for torrent and NZB files
+    pub watch_folder: String,
+    /// Whether watched files are deleted after their task was created
+    pub delete_watched_files: bool,
+}
+
+/// Partial update of [`LocationSettings`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct LocationSettingsUpdate {
+    pub enable_watch_folder: Option<bool>,
+    pub watch_folder: Option<String>,
+    pub delete_watched_files: Option<bool>,
+}
+
+impl Settings for LocationSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.Location";
+    type Update = LocationSettingsUpdate;
+}
+
+impl SettingsUpdate for LocationSettingsUpdate {
+    type Settings = LocationSettings;
+}
+
+/// Speed applied during one hour of the [`WeeklySchedule`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SpeedMode {
+    /// Downloads are stopped
+    Off,
+    /// The regular speed limits apply
+    #[default]
+    Normal,
+    /// The alternative speed limits of [`SchedulerSettings`] apply
+    Alternative,
+}
+
+impl SpeedMode {
+    /// Character representing the mode in the schedule sent to the API
+    fn as_char(self) -> char {
+        match self {
+            SpeedMode::Off => '0',
+            SpeedMode::Normal => '1',
+            SpeedMode::Alternative => '2',
+        }
+    }
+
+    fn from_char(value: char) -> Option<Self> {
+        match value {
+            '0' => Some(SpeedMode::Off),
+            '1' => Some(SpeedMode::Normal),
+            '2' => Some(SpeedMode::Alternative),
+            _ => None,
+        }
+    }
+}
+
+/// Speed mode for every hour of the week, starting on Sunday 00:00
+///
+/// ```rust
+/// # use chrono::Weekday;
+/// # use syno_download_station::settings::{SpeedMode, WeeklySchedule};
+/// let mut schedule = WeeklySchedule::default();
+/// schedule
+///     .set_hours(WeeklySchedule::WEEKDAYS, 9..18, SpeedMode::Alternative)
+///     .unwrap();
+/// assert_eq!(schedule.get(Weekday::Mon, 9), Some(SpeedMode::Alternative));
+/// assert_eq!(schedule.get(Weekday::Mon, 18), Some(SpeedMode::Normal));
+/// assert_eq!(schedule.get(Weekday::Sat, 12), Some(SpeedMode::Normal));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WeeklySchedule([[SpeedMode; 24]; 7]);
+
+impl WeeklySchedule {
+    /// Monday to Friday
+    pub const WEEKDAYS: [Weekday; 5] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ];
+
+    /// Saturday and Sunday
+    pub const WEEKEND: [Weekday; 2] = [Weekday::Sat, Weekday::Sun];
+
+    /// Creates a schedule using the same mode for the whole week
+    #[must_use]
+    pub fn new(mode: SpeedMode) -> Self {
+        WeeklySchedule([[mode; 24]; 7])
+    }
+
+    /// Returns the mode of the given hour, `None` if the hour is past 23
+    #[must_use]
+    pub fn get(&self, day: Weekday, hour: u8) -> Option<SpeedMode> {
+        self.0[day.num_days_from_sunday() as usize]
+            .get(usize::from(hour))
+            .copied()
+    }
+
+    /// Sets the mode of one hour
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hour is past 23
+    pub fn set(&mut self, day: Weekday, hour: u8, mode: SpeedMode) -> Result<()> {
+        self.set_hours([day], hour..hour.saturating_add(1), mode)
+    }
+
+    /// Sets the mode of a range of hours on several days, e.g. `9..18` for 09:00 to 18:00
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range ends past 24 or starts after it ends
+    pub fn set_hours(
+        &mut self,
+        days: impl IntoIterator<Item = Weekday>,
+        hours: Range<u8>,
+        mode: SpeedMode,
+    ) -> Result<()> {
+        if hours.start > hours.end || hours.end > 24 {
+            return Err(InvalidInput(format!(
+                "Hours must be within 0..24, got: {}..{}",
+                hours.start, hours.end
+            ))
+            .into());
+        }
+
+        for day in days {
+            let slots = &mut self.0[day.num_days_from_sunday() as usize];
+            for slot in &mut slots[usize::from(hours.start)..usize::from(hours.end)] {
+                *slot = mode;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for WeeklySchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0
+            .iter()
+            .flatten()
+            .try_for_each(|mode| f.write_char(mode.as_char()))
+    }
+}
+
+impl FromStr for WeeklySchedule {
+    type Err = SynoError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.chars().count() != 7 * 24 {
+            return Err(InvalidInput(format!(
+                "Schedule must have 168 hours, got {}",
+                value.chars().count()
+            )));
+        }
+
+        let mut schedule = WeeklySchedule::default();
+        for (slot, value) in schedule.0.iter_mut().flatten().zip(value.chars()) {
+            *slot = SpeedMode::from_char(value)
+                .ok_or_else(|| InvalidInput(format!("Unknown schedule speed mode: {value}")))?;
+        }
+
+        Ok(schedule)
+    }
+}
+
+/// Used when deserializing a schedule sent by the NAS, so a malformed schedule is
+/// reported as [`SynoError::InvalidResponse`]
+impl TryFrom<String> for WeeklySchedule {
+    type Error = SynoError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse().map_err(|error| match error {
+            InvalidInput(message) => InvalidResponse(message),
+            error => error,
+        })
+    }
+}
+
+impl From<WeeklySchedule> for String {
+    fn from(schedule: WeeklySchedule) -> Self {
+        schedule.to_string()
+    }
+}
+
+/// Bandwidth scheduler settings (`SYNO.DownloadStation2.Settings.Scheduler`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SchedulerSettings {
+    /// Whether the schedule is applied
+    pub enabled: bool,
+    /// Speed mode for every hour of the week
+    pub schedule: WeeklySchedule,
+    /// Alternative maximum download rate in KB/s, 0 for unlimited
+    pub alt_download_rate: u32,
+    /// Alternative maximum upload rate in KB/s, 0 for unlimited
+    pub alt_upload_rate: u32,
+}
+
+/// Partial update of [`SchedulerSettings`]
+///
+/// A full update converted from [`SchedulerSettings`] copies the schedule of one NAS to
+/// another.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SchedulerSettingsUpdate {
+    pub enabled: Option<bool>,
+    pub schedule: Option<WeeklySchedule>,
+    pub alt_download_rate: Option<u32>,
+    pub alt_upload_rate: Option<u32>,
+}
+
+impl From<SchedulerSettings> for SchedulerSettingsUpdate {
+    fn from(settings: SchedulerSettings) -> Self {
+        SchedulerSettingsUpdate {
+            enabled: Some(settings.enabled),
+            schedule: Some(settings.schedule),
+            alt_download_rate: Some(settings.alt_download_rate),
+            alt_upload_rate: Some(settings.alt_upload_rate),
+        }
+    }
+}
+
+impl Settings for SchedulerSettings {
+    const API_NAME: &'static str = "SYNO.DownloadStation2.Settings.Scheduler";
+    type Update = SchedulerSettingsUpdate;
+}
+
+impl SettingsUpdate for SchedulerSettingsUpdate {
+    type Settings = SchedulerSettings;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nzb_update_debug_redacts_password() {
+        let update = NzbSettingsUpdate {
+            username: Some("reader".into()),
+            password: Some("hunter2".into()),
+            ..Default::default()
+        };
+
+        let debug = format!("{update:?}");
+        assert!(debug.contains("reader"));
+        assert!(debug.contains("***"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_schedule_round_trip() {
+        let mut schedule = WeeklySchedule::new(SpeedMode::Normal);
+        schedule.set(Weekday::Sun, 0, SpeedMode::Off).unwrap();
+        schedule
+            .set_hours(WeeklySchedule::WEEKEND, 22..24, SpeedMode::Alternative)
+            .unwrap();
+
+        let value = schedule.to_string();
+        assert_eq!(value.len(), 168);
+        assert!(value.starts_with("011111"));
+        assert!(value.ends_with("1122"));
+        assert_eq!(value.parse::<WeeklySchedule>().unwrap(), schedule);
+    }
+
+    #[test]
+    fn test_schedule_rejects_invalid_input() {
+        let mut schedule = WeeklySchedule::default();
+        assert_eq!(schedule.get(Weekday::Mon, 24), None);
+        assert_eq!(schedule.get(Weekday::Mon, 30), None);
+        assert!(schedule.set(Weekday::Mon, 24, SpeedMode::Off).is_err());
+        assert!(
+            schedule
+                .set_hours([Weekday::Mon], 20..25, SpeedMode::Off)
+                .is_err()
+        );
+        assert_eq!(schedule, WeeklySchedule::default());
+
+        assert!(matches!(
+            "012".parse::<WeeklySchedule>(),
+            Err(SynoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            "3".repeat(168).parse::<WeeklySchedule>(),
+            Err(SynoError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            WeeklySchedule::try_from("012".to_string()),
+            Err(SynoError::InvalidResponse(_))
+        ));
+    }
+}
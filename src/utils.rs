@@ -1,20 +1,17 @@
-use crate::entities::Task;
-use crate::entities::TaskStatus::{Downloading, Seeding};
+use crate::entities::{Task, TaskStatus, Tasks, TasksSummary, Transfer};
 use byte_unit::{Byte, UnitType};
+use std::time::Duration;
 
 impl Task {
     #[must_use]
     pub fn calculate_size(&self) -> String {
-        let size = Byte::from(self.size);
-        format!("{:#.2}", size.get_appropriate_unit(UnitType::Decimal))
+        TaskFormatter::default().format_bytes(self.size)
     }
 
     #[allow(clippy::cast_precision_loss)]
     #[must_use]
     pub fn calculate_progress(&self) -> f64 {
-        self.additional
-            .as_ref()
-            .and_then(|additional| additional.transfer.as_ref())
+        self.transfer()
             .map(|transfer| {
                 let size_downloaded = transfer.size_downloaded;
                 (size_downloaded as f64 / self.size as f64 * 100.0).round()
@@ -25,65 +22,288 @@ impl Task {
 
     #[must_use]
     pub fn calculate_speed(&self) -> String {
-        if !matches!(self.status, Downloading) && !matches!(self.status, Seeding) {
-            return String::new();
+        TaskFormatter::default().format_speed(self)
+    }
+
+    #[must_use]
+    pub fn calculate_time_left(&self) -> String {
+        TaskFormatter::default().format_time_left_of(self, self.whole_time_left())
+    }
+
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn calculate_ratio(&self) -> f64 {
+        self.transfer()
+            .filter(|a| a.size_downloaded > 0)
+            .map(|t| t.size_uploaded as f64 / t.size_downloaded as f64)
+            .unwrap_or_default()
+    }
+
+    /// Download speed in bytes per second, 0 without transfer information
+    #[must_use]
+    pub fn download_speed(&self) -> u64 {
+        self.transfer()
+            .map(|transfer| transfer.speed_download)
+            .unwrap_or_default()
+    }
+
+    /// Upload speed in bytes per second, 0 without transfer information
+    #[must_use]
+    pub fn upload_speed(&self) -> u64 {
+        self.transfer()
+            .map(|transfer| transfer.speed_upload)
+            .unwrap_or_default()
+    }
+
+    /// Speed that matters for the status: the download speed while downloading, the
+    /// upload speed while seeding, `None` otherwise
+    #[must_use]
+    pub fn current_speed(&self) -> Option<u64> {
+        match self.status {
+            TaskStatus::Downloading => Some(self.download_speed()),
+            TaskStatus::Seeding => Some(self.upload_speed()),
+            _ => None,
+        }
+    }
+
+    /// Bytes left to download, counting only the wanted files when file information is
+    /// available. `None` without transfer or file information, or when more bytes were
+    /// downloaded than the reported size, as the size is then not reliable.
+    #[must_use]
+    pub fn remaining_bytes(&self) -> Option<u64> {
+        if let Some((size, downloaded)) = self.wanted_files_size() {
+            return size.checked_sub(downloaded);
+        }
+
+        self.transfer()
+            .and_then(|transfer| self.size.checked_sub(transfer.size_downloaded))
+    }
+
+    /// Downloaded part of the task between 0.0 and 1.0, counting only the wanted files
+    /// when file information is available. `None` without transfer or file information,
+    /// or for an empty task.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn progress(&self) -> Option<f64> {
+        let (size, downloaded) = self.wanted_files_size().or_else(|| {
+            self.transfer()
+                .map(|transfer| (self.size, transfer.size_downloaded))
+        })?;
+
+        (size > 0).then(|| (downloaded as f64 / size as f64).min(1.0))
+    }
+
+    /// Estimated time until the download is done, `None` if the task is not
+    /// downloading or the time can't be estimated
+    #[must_use]
+    pub fn time_left(&self) -> Option<Duration> {
+        if self.status != TaskStatus::Downloading {
+            return None;
+        }
+
+        let speed = self.download_speed();
+        if speed == 0 {
+            return None;
+        }
+        self.remaining_bytes()
+            .map(|remaining| Duration::from_secs(remaining / speed))
+    }
+
+    /// Estimated time until the whole task is downloaded, wanted or not, as shown by
+    /// [`Task::calculate_time_left`]
+    fn whole_time_left(&self) -> Option<Duration> {
+        if self.status != TaskStatus::Downloading {
+            return None;
         }
 
+        let speed = self.download_speed();
+        if speed == 0 {
+            return None;
+        }
+        self.transfer()
+            .and_then(|transfer| self.size.checked_sub(transfer.size_downloaded))
+            .map(|remaining| Duration::from_secs(remaining / speed))
+    }
+
+    fn transfer(&self) -> Option<&Transfer> {
         self.additional
             .as_ref()
             .and_then(|additional| additional.transfer.as_ref())
-            .map(|transfer| match self.status {
-                Downloading => transfer.speed_download,
-                Seeding => transfer.speed_upload,
-                _ => 0,
-            })
-            .take_if(|speed| *speed > 0u64)
-            .map(|speed| {
-                format!(
-                    "({:#.2}/s)",
-                    Byte::from(speed).get_appropriate_unit(UnitType::Decimal)
+    }
+
+    /// Total and downloaded size of the wanted files, `None` without file information
+    fn wanted_files_size(&self) -> Option<(u64, u64)> {
+        let files = self
+            .additional
+            .as_ref()
+            .and_then(|additional| additional.file.as_ref())
+            .filter(|files| !files.is_empty())?;
+
+        Some(files.iter().filter(|file| file.wanted).fold(
+            (0u64, 0u64),
+            |(size, downloaded), file| {
+                (
+                    size.saturating_add(file.size),
+                    downloaded.saturating_add(file.size_downloaded),
                 )
+            },
+        ))
+    }
+}
+
+/// Units used by [`TaskFormatter`] for byte counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteUnits {
+    /// Powers of 1000: KB, MB, GB
+    #[default]
+    Decimal,
+    /// Powers of 1024: KiB, MiB, GiB
+    Binary,
+}
+
+/// Formats task sizes, speeds and times left as text
+///
+/// Templates are plain strings with a placeholder replaced by the formatted value:
+/// `{speed}` in the speed template and `{time_left}` in the time left template.
+/// The default formatter produces the strings of [`Task::calculate_speed`] and, as
+/// times left are estimated from the wanted files only, of [`Task::calculate_time_left`]
+/// for tasks without file information.
+///
+/// ```rust
+/// # use syno_download_station::utils::{ByteUnits, TaskFormatter};
+/// let formatter = TaskFormatter::default()
+///     .units(ByteUnits::Binary)
+///     .precision(1)
+///     .speed_template("{speed}/s");
+/// assert_eq!(formatter.format_bytes(1_234_567_890), "1.1 GiB");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TaskFormatter {
+    units: ByteUnits,
+    precision: usize,
+    speed_template: String,
+    time_left_template: String,
+}
+
+impl Default for TaskFormatter {
+    fn default() -> Self {
+        Self {
+            units: ByteUnits::Decimal,
+            precision: 2,
+            speed_template: "({speed}/s)".into(),
+            time_left_template: "⏳Time left: {time_left}".into(),
+        }
+    }
+}
+
+impl TaskFormatter {
+    /// Sets the units of byte counts, decimal by default
+    #[must_use]
+    pub fn units(mut self, units: ByteUnits) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Sets the number of decimals of byte counts, 2 by default
+    #[must_use]
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the template of speeds, `({speed}/s)` by default
+    #[must_use]
+    pub fn speed_template(mut self, template: impl Into<String>) -> Self {
+        self.speed_template = template.into();
+        self
+    }
+
+    /// Sets the template of times left, `⏳Time left: {time_left}` by default
+    #[must_use]
+    pub fn time_left_template(mut self, template: impl Into<String>) -> Self {
+        self.time_left_template = template.into();
+        self
+    }
+
+    /// Formats a number of bytes with the most appropriate unit, e.g. `1.23 GB`
+    #[must_use]
+    pub fn format_bytes(&self, bytes: u64) -> String {
+        let unit_type = match self.units {
+            ByteUnits::Decimal => UnitType::Decimal,
+            ByteUnits::Binary => UnitType::Binary,
+        };
+        let bytes = Byte::from(bytes).get_appropriate_unit(unit_type);
+        format!("{bytes:#.precision$}", precision = self.precision)
+    }
+
+    /// Formats a duration in days, hours, minutes and seconds, e.g. `3 h 28 m`
+    #[must_use]
+    pub fn format_duration(&self, duration: Duration) -> String {
+        convert_time_left(i64::try_from(duration.as_secs()).unwrap_or(i64::MAX))
+    }
+
+    /// Formats the current speed of the task with the speed template, empty if the task
+    /// is not downloading or seeding or doesn't transfer anything
+    #[must_use]
+    pub fn format_speed(&self, task: &Task) -> String {
+        task.current_speed()
+            .filter(|speed| *speed > 0)
+            .map(|speed| {
+                self.speed_template
+                    .replace("{speed}", &self.format_bytes(speed))
             })
             .unwrap_or_default()
     }
 
-    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
-    pub fn calculate_time_left(&self) -> String {
-        if !matches!(self.status, Downloading) {
+    /// Formats the time left of a downloading task with the time left template, empty
+    /// if the task is not downloading or has no transfer information
+    #[must_use]
+    pub fn format_time_left(&self, task: &Task) -> String {
+        self.format_time_left_of(task, task.time_left())
+    }
+
+    /// Formats the given time left of a downloading task with the time left template
+    fn format_time_left_of(&self, task: &Task, time_left: Option<Duration>) -> String {
+        if task.status != TaskStatus::Downloading || task.transfer().is_none() {
             return String::new();
         }
-        self.additional
-            .as_ref()
-            .and_then(|additional| additional.transfer.as_ref())
-            .map(|transfer| match self.status {
-                Downloading => {
-                    let speed_download = transfer.speed_download;
-                    let total_size = self.size;
-                    let size_downloaded = transfer.size_downloaded;
-
-                    if speed_download == 0 {
-                        -1i64
-                    } else {
-                        ((total_size as f64 - size_downloaded as f64) / speed_download as f64)
-                            .floor() as i64
-                    }
-                }
-                _ => -1i64,
-            })
-            .map(convert_time_left)
-            .map(|time_left| format!("⏳Time left: {time_left}"))
-            .unwrap_or_default()
+
+        let time_left = time_left.map_or_else(
+            || convert_time_left(-1),
+            |time_left| self.format_duration(time_left),
+        );
+        self.time_left_template.replace("{time_left}", &time_left)
     }
+}
 
-    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+impl Tasks {
+    /// Counts the tasks per status and sums their sizes, remaining bytes and ratios.
+    /// Remaining bytes and ratios need the tasks to be fetched with
+    /// [`crate::entities::AdditionalField::Transfer`], tasks without it are left out.
+    #[allow(clippy::cast_precision_loss)]
     #[must_use]
-    pub fn calculate_ratio(&self) -> f64 {
-        self.additional
-            .as_ref()
-            .and_then(|a| a.transfer.as_ref())
-            .filter(|a| a.size_downloaded > 0)
-            .map(|t| t.size_uploaded as f64 / t.size_downloaded as f64)
-            .unwrap_or_default()
+    pub fn summary(&self) -> TasksSummary {
+        let mut summary = TasksSummary::default();
+        let mut ratio_sum = 0.0;
+        let mut ratio_count = 0usize;
+
+        for task in &self.task {
+            *summary.status_counts.entry(task.status).or_default() += 1;
+            summary.total_size = summary.total_size.saturating_add(task.size);
+
+            if task.transfer().is_some() {
+                summary.remaining = summary
+                    .remaining
+                    .saturating_add(task.remaining_bytes().unwrap_or_default());
+                ratio_sum += task.calculate_ratio();
+                ratio_count += 1;
+            }
+        }
+
+        if ratio_count > 0 {
+            summary.mean_ratio = Some(ratio_sum / ratio_count as f64);
+        }
+        summary
     }
 }
 
@@ -120,7 +340,8 @@ pub fn convert_time_left(input: i64) -> String {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
-    use crate::entities::{AdditionalTaskInfo, Transfer};
+    use crate::entities::TaskStatus::{Downloading, Seeding};
+    use crate::entities::{AdditionalTaskInfo, File, FilePriority};
 
     impl Task {
         fn create_test_task() -> Task {
@@ -201,6 +422,137 @@ mod tests {
         assert_eq!(0.7416979140808425, task.calculate_ratio());
     }
 
+    #[test]
+    fn test_numeric_transfer_values() {
+        let mut task = Task::create_test_task();
+        task.additional
+            .as_mut()
+            .unwrap()
+            .transfer
+            .as_mut()
+            .unwrap()
+            .size_downloaded = 234_567_890;
+
+        assert_eq!(98765, task.download_speed());
+        assert_eq!(Some(98765), task.current_speed());
+        assert_eq!(Some(1_000_000_000), task.remaining_bytes());
+        assert_eq!(
+            Some(0.19),
+            task.progress().map(|p| (p * 100.0).round() / 100.0)
+        );
+        assert_eq!(Some(Duration::from_secs(10125)), task.time_left());
+
+        task.status = Seeding;
+        assert_eq!(Some(0), task.current_speed());
+        assert_eq!(None, task.time_left());
+
+        task.additional = None;
+        assert_eq!(None, task.remaining_bytes());
+        assert_eq!(None, task.progress());
+    }
+
+    #[test]
+    fn test_progress_of_wanted_files() {
+        let file = |size, size_downloaded, wanted| File {
+            filename: String::from("file"),
+            index: 0,
+            priority: FilePriority::Normal,
+            size,
+            size_downloaded,
+            wanted,
+        };
+        let mut task = Task::create_test_task();
+        task.additional.as_mut().unwrap().file = Some(vec![
+            file(1000, 500, true),
+            file(3000, 0, true),
+            file(6000, 0, false),
+        ]);
+
+        assert_eq!(Some(0.125), task.progress());
+        assert_eq!(Some(3500), task.remaining_bytes());
+        assert_eq!(Some(Duration::from_secs(0)), task.time_left());
+        // The time left as text still counts the whole task
+        assert_eq!("⏳Time left: 3 h 28 m", task.calculate_time_left());
+    }
+
+    #[test]
+    fn test_time_left_when_downloaded_exceeds_size() {
+        let mut task = Task::create_test_task();
+        task.additional
+            .as_mut()
+            .unwrap()
+            .transfer
+            .as_mut()
+            .unwrap()
+            .size_downloaded = task.size + 1;
+
+        assert_eq!(None, task.remaining_bytes());
+        assert_eq!(None, task.time_left());
+        assert_eq!("⏳Time left: Unknown", task.calculate_time_left());
+    }
+
+    #[test]
+    fn test_task_formatter() {
+        let task = Task::create_test_task();
+        let formatter = TaskFormatter::default()
+            .units(ByteUnits::Binary)
+            .precision(1)
+            .speed_template("{speed}/s")
+            .time_left_template("ETA {time_left}");
+
+        assert_eq!("1.1 GiB", formatter.format_bytes(task.size));
+        assert_eq!("96.5 KiB/s", formatter.format_speed(&task));
+        assert_eq!("ETA 3 h 28 m", formatter.format_time_left(&task));
+        assert_eq!(
+            "1 d 0 h 1 m",
+            formatter.format_duration(Duration::from_secs(86_460))
+        );
+
+        let mut paused = Task::create_test_task();
+        paused.status = crate::entities::TaskStatus::Paused;
+        assert_eq!("", formatter.format_speed(&paused));
+        assert_eq!("", formatter.format_time_left(&paused));
+    }
+
+    #[test]
+    fn test_tasks_summary() {
+        let downloading = Task::create_test_task();
+        let mut seeding = Task::create_test_task();
+        seeding.status = Seeding;
+        seeding.size = 1000;
+        seeding.additional = Some(AdditionalTaskInfo {
+            transfer: Some(Transfer {
+                size_downloaded: 1000,
+                size_uploaded: 1500,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut without_transfer = Task::create_test_task();
+        without_transfer.additional = None;
+
+        let tasks = Tasks {
+            offset: 0,
+            task: vec![downloading, seeding, without_transfer],
+            total: 3,
+        };
+        let summary = tasks.summary();
+
+        assert_eq!(2, summary.count(Downloading));
+        assert_eq!(1, summary.count(Seeding));
+        assert_eq!(0, summary.count(crate::entities::TaskStatus::Paused));
+        assert_eq!(2_469_136_780, summary.total_size);
+        assert_eq!(1_234_567_890, summary.remaining);
+        assert_eq!(Some(0.75), summary.mean_ratio);
+
+        let empty = Tasks {
+            offset: 0,
+            task: Vec::new(),
+            total: 0,
+        };
+        assert_eq!(None, empty.summary().mean_ratio);
+    }
+
     #[test]
     fn test_calculate_ratio_when_ratio_is_zero() {
         let mut task = Task::create_test_task();
@@ -1,18 +1,40 @@
 mod utils;
 
 use crate::utils::body_from_file;
-use std::fs;
-use syno_download_station::client::{SynoDS, SynoError};
+use chrono::{Utc, Weekday};
+use std::time::Duration;
+use std::{env, fs};
+use syno_download_station::client::{
+    Backend, CreateTaskOptions, SynoDS, SynoError, TaskEvent, TaskLimitsUpdate,
+};
+use syno_download_station::entities::{
+    AdditionalField, AdditionalFields, FilePriority, RssFilter, ServiceStatus, SortField,
+    SortOrder, TaskStatus,
+};
+use syno_download_station::error_code::ErrorCode;
+use syno_download_station::session::{FileSessionStore, Session};
+use syno_download_station::settings::{
+    BtSettings, BtSettingsUpdate, GlobalSettingsUpdate, SchedulerSettings, SchedulerSettingsUpdate,
+    SpeedMode, WeeklySchedule,
+};
+use tokio_stream::StreamExt;
 use utils::form_param;
 use wiremock::matchers::{header, header_regex, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 // Helper function to create a client with a mock server
 async fn setup_client() -> (MockServer, SynoDS) {
+    setup_client_with_api_info("test-files/api_info_success.json").await
+}
+
+// Helper function to create a client with a mock server reporting the given API info
+async fn setup_client_with_api_info(api_info_file: &str) -> (MockServer, SynoDS) {
     // Start a lightweight mock server.
     let server = MockServer::start().await;
     let url = server.uri();
 
+    create_api_info_mock(&server, api_info_file).await;
+
     let synods = SynoDS::builder()
         .url(url)
         .username("test")
@@ -23,6 +45,31 @@ async fn setup_client() -> (MockServer, SynoDS) {
     (server, synods)
 }
 
+// Helper function to create a mock for the API info query, served at the path the
+// response reports for SYNO.API.Info
+async fn create_api_info_mock(server: &MockServer, response_file: &str) {
+    let api_info: serde_json::Value =
+        serde_json::from_str(&body_from_file(response_file)).expect("Invalid API info file");
+    let info_path = api_info["data"]["SYNO.API.Info"]["path"]
+        .as_str()
+        .expect("API info file without SYNO.API.Info path");
+
+    Mock::given(method("POST"))
+        .and(path(format!("/webapi/{info_path}")))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(form_param("api", "SYNO.API.Info"))
+        .and(form_param("version", "1"))
+        .and(form_param("method", "query"))
+        .and(form_param("query", "all"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file(response_file)),
+        )
+        .mount(server)
+        .await;
+}
+
 // Helper function to create a mock for login
 async fn create_login_mock(server: &mut MockServer) {
     // Create a mock on the server.
@@ -122,6 +169,89 @@ async fn test_get_tasks() {
     assert_eq!(tasks.task[1].title, "Test Torrent 2");
 }
 
+#[tokio::test]
+async fn test_list_tasks_with_filters() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "list"),
+        ("offset", "250"),
+        ("limit", "10"),
+        ("sort_by", r#""created_time""#),
+        ("order", r#""DESC""#),
+        ("status", "[1,2]"),
+        ("additional", r#"["transfer"]"#),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/list_tasks_page_1.json").await;
+
+    let tasks = synods
+        .list_tasks()
+        .offset(250)
+        .limit(10)
+        .sort_by(SortField::CreatedTime, SortOrder::Descending)
+        .status(&[TaskStatus::Waiting, TaskStatus::Downloading])
+        .additional(AdditionalField::Transfer)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(tasks.offset, 250);
+    assert_eq!(tasks.total, 253);
+    assert_eq!(tasks.task.len(), 2);
+}
+
+#[tokio::test]
+async fn test_list_tasks_pages() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    for (offset, file) in [
+        ("250", "test-files/list_tasks_page_1.json"),
+        ("252", "test-files/list_tasks_page_2.json"),
+    ] {
+        let params = vec![("method", "list"), ("offset", offset), ("limit", "2")];
+        create_api_mock(&mut server, params, file).await;
+    }
+
+    let mut pages = synods
+        .list_tasks()
+        .offset(250)
+        .limit(2)
+        .additional(AdditionalFields::NONE)
+        .pages();
+
+    let mut ids = Vec::new();
+    while let Some(page) = pages.next_page().await {
+        ids.extend(page.unwrap().task.into_iter().map(|task| task.id));
+    }
+
+    assert_eq!(ids, ["task_id_251", "task_id_252", "task_id_253"]);
+    assert!(pages.next_page().await.is_none());
+
+    let pages = synods
+        .list_tasks()
+        .offset(250)
+        .limit(2)
+        .additional(AdditionalFields::NONE)
+        .pages()
+        .into_stream()
+        .collect::<Vec<_>>()
+        .await;
+    let ids = pages
+        .into_iter()
+        .flat_map(|page| page.unwrap().task)
+        .map(|task| task.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["task_id_251", "task_id_252", "task_id_253"]);
+}
+
 #[tokio::test]
 async fn test_get_task() {
     let (mut server, synods) = setup_client().await;
@@ -161,6 +291,7 @@ async fn test_get_task() {
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].filename, "test_file_1.mp4");
         assert_eq!(files[0].size, 1_073_741_824);
+        assert_eq!(files[0].priority, FilePriority::Normal);
     } else {
         panic!("File information missing");
     }
@@ -184,446 +315,2928 @@ async fn test_get_task() {
 }
 
 #[tokio::test]
-async fn test_create_task() {
+async fn test_get_task_with_selected_fields() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let uri = "https://example.com/test.iso";
-    let destination = "/downloads";
-
     let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "create"),
-        ("type", "\"url\""),
-        ("destination", destination),
-        ("url", uri),
-        ("create_list", "false"),
+        ("method", "get"),
+        ("id", "task_id_1"),
+        ("additional", r#"["transfer","detail"]"#),
     ];
 
-    create_api_mock(&mut server, params, "test-files/create_task_success.json").await;
-
-    let result = synods.create_task(uri, destination).await;
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/get_task_transfer_only.json",
+    )
+    .await;
 
-    server.verify().await;
+    let task_info = synods
+        .get_task_with(
+            vec!["task_id_1".to_string()],
+            AdditionalField::Detail | AdditionalField::Transfer,
+        )
+        .await
+        .unwrap();
 
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    let additional = task_info.task[0].additional.as_ref().unwrap();
+    assert_eq!(
+        additional.transfer.as_ref().unwrap().speed_download,
+        1_048_576
+    );
+    assert!(additional.detail.is_none());
+    assert!(additional.file.is_none());
 }
 
 #[tokio::test]
-async fn test_create_task_from_file() {
+async fn test_set_files_wanted_and_priority() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let file_path = "test-files/test.torrent";
-    let file_name = "test.torrent";
-    let destination = "/downloads";
-
-    // Read the test file
-    let file_data = fs::read(file_path).expect("Failed to read test file");
-
-    // Create a mock for the file upload
-    create_file_upload_mock(&mut server, "test-files/create_task_from_file_success.json").await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.BT.File"),
+        ("version", "2"),
+        ("method", "set"),
+        ("task_id", "\"task_id_1\""),
+        ("index", "[1,2]"),
+        ("wanted", "false"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    let result = synods
-        .create_task_from_file(&file_data, file_name, destination)
-        .await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.BT.File"),
+        ("method", "set"),
+        ("task_id", "\"task_id_1\""),
+        ("index", "[0]"),
+        ("priority", "\"high\""),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    server.verify().await;
+    synods
+        .set_files_wanted("task_id_1", &[1, 2], false)
+        .await
+        .unwrap();
+    synods
+        .set_files_priority("task_id_1", &[0], FilePriority::High)
+        .await
+        .unwrap();
 
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    let error = synods
+        .set_files_priority("task_id_1", &[], FilePriority::Low)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
 }
 
 #[tokio::test]
-async fn test_pause() {
+async fn test_add_and_remove_trackers() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let task_id = "task_id_1";
-
     let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "pause"),
-        ("id", task_id),
+        ("api", "SYNO.DownloadStation2.Task.BT.Tracker"),
+        ("method", "add"),
+        ("task_id", "\"task_id_1\""),
+        (
+            "tracker",
+            r#"["https://a.example.com/announce","udp://b.example.com:80"]"#,
+        ),
     ];
-
     create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    let result = synods.pause(task_id).await;
-
-    server.verify().await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.BT.Tracker"),
+        ("method", "delete"),
+        ("task_id", "\"task_id_1\""),
+        ("tracker", r#"["https://a.example.com/announce"]"#),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    synods
+        .add_trackers(
+            "task_id_1",
+            &["https://a.example.com/announce", "udp://b.example.com:80"],
+        )
+        .await
+        .unwrap();
+    synods
+        .remove_trackers("task_id_1", &["https://a.example.com/announce"])
+        .await
+        .unwrap();
 }
 
 #[tokio::test]
-async fn test_resume() {
+async fn test_replace_tracker_host() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let task_id = "task_id_1";
+    let params = vec![("method", "list"), ("additional", r#"["tracker"]"#)];
+    create_api_mock(&mut server, params, "test-files/get_tasks_trackers.json").await;
 
     let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "resume"),
-        ("id", task_id),
+        ("method", "add"),
+        ("task_id", "\"task_id_1\""),
+        (
+            "tracker",
+            r#"["https://tracker.example.org/announce?passkey=abc"]"#,
+        ),
     ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    create_api_mock(&mut server, params, "test-files/resume_success.json").await;
+    let params = vec![
+        ("method", "delete"),
+        ("task_id", "\"task_id_1\""),
+        (
+            "tracker",
+            r#"["https://tracker.example.com/announce?passkey=abc"]"#,
+        ),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    let result = synods.resume(task_id).await;
+    // URLs without a path or with characters the URL parser would encode are kept as is
+    let params = vec![
+        ("method", "add"),
+        ("task_id", "\"task_id_2\""),
+        (
+            "tracker",
+            r#"["http://tracker.example.org:6969","https://tracker.example.org/announce?passkey=a'b"]"#,
+        ),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    server.verify().await;
+    let params = vec![
+        ("method", "delete"),
+        ("task_id", "\"task_id_2\""),
+        (
+            "tracker",
+            r#"["http://tracker.example.com:6969","https://tracker.example.com/announce?passkey=a'b"]"#,
+        ),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    let params = vec![("method", "add"), ("task_id", "\"task_id_3\"")];
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
 
-    // Verify the response data
-    assert_eq!(result.unwrap().failed_task.len(), 0);
+    let replacements = synods
+        .replace_tracker_host("tracker.example.com", "tracker.example.org")
+        .await
+        .unwrap();
+
+    assert_eq!(replacements.len(), 3);
+    assert_eq!(replacements[0].task_id, "task_id_1");
+    assert!(replacements[0].is_success());
+    assert_eq!(
+        replacements[0].new_urls,
+        ["https://tracker.example.org/announce?passkey=abc"]
+    );
+
+    assert_eq!(replacements[1].task_id, "task_id_2");
+    assert!(replacements[1].is_success());
+    assert_eq!(
+        replacements[1].new_urls,
+        [
+            "http://tracker.example.org:6969",
+            "https://tracker.example.org/announce?passkey=a'b"
+        ]
+    );
+
+    assert_eq!(replacements[2].task_id, "task_id_3");
+    let error = replacements[2].error.as_ref().unwrap();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::Api { code: 403, .. })
+    ));
+
+    // Invalid hosts are rejected before any task is edited
+    for new_host in ["tracker.example.org:8080", "tracker example.org", "a/b"] {
+        let error = synods
+            .replace_tracker_host("tracker.example.com", new_host)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SynoError>(),
+            Some(SynoError::InvalidInput(_))
+        ));
+    }
 }
 
 #[tokio::test]
-async fn test_complete() {
+async fn test_edit_task_destination() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let task_id = "task_id_1";
-
     let params = vec![
-        ("api", "SYNO.DownloadStation2.Task.Complete"),
-        ("version", "1"),
-        ("method", "start"),
-        ("id", task_id),
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "edit"),
+        ("id", "task_id_1,task_id_2"),
+        ("destination", "\"downloads/movies\""),
     ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    create_api_mock(&mut server, params, "test-files/complete_success.json").await;
-
-    let result = synods.complete(task_id).await;
-
-    server.verify().await;
+    let params = vec![
+        ("method", "edit"),
+        ("id", "task_id_3"),
+        ("destination", "\"missing\""),
+    ];
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
 
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    synods
+        .edit_task_destination(&["task_id_1", "task_id_2"], "downloads/movies")
+        .await
+        .unwrap();
 
-    // Verify the response data
-    let task_completed = result.unwrap();
-    assert_eq!(task_completed.task_id, "task_id_1");
+    let error = synods
+        .edit_task_destination(&["task_id_3"], "missing")
+        .await
+        .unwrap_err();
+    let error = error.downcast_ref::<SynoError>().unwrap();
+    assert!(
+        matches!(error, SynoError::DestinationNotFound { destination } if destination == "missing")
+    );
+    assert_eq!(error.task_status(), Some(TaskStatus::ErrorDestNoExist));
+    assert_eq!(error.error_code(), Some(ErrorCode::DestinationNotFound));
+
+    let error = synods
+        .edit_task_destination(&["task_id_1"], "downloads/../etc")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
 }
 
 #[tokio::test]
-async fn test_delete_task() {
+async fn test_get_and_set_task_limits() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let task_id = "task_id_1";
-    let force_complete = true;
-
     let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "delete"),
-        ("id", task_id),
-        ("force_complete", "true"),
+        ("api", "SYNO.DownloadStation2.Task.BT"),
+        ("method", "get"),
+        ("task_id", "\"task_id_1\""),
     ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/get_task_limits_success.json",
+    )
+    .await;
 
-    create_api_mock(&mut server, params, "test-files/delete_task_success.json").await;
-
-    let result = synods.delete_task(task_id, force_complete).await;
-
-    server.verify().await;
-
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.BT"),
+        ("method", "set"),
+        ("task_id", "\"task_id_1\""),
+        ("max_upload_rate", "256"),
+        ("seeding_ratio", "2"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    // Verify the response data
-    let task_operation = result.unwrap();
-    assert_eq!(task_operation.failed_task.len(), 0);
+    let limits = synods.get_task_limits("task_id_1").await.unwrap();
+    assert_eq!(limits.max_download_rate, 0);
+    assert_eq!(limits.max_upload_rate, 512);
+    assert!((limits.seeding_ratio - 1.5).abs() < f64::EPSILON);
+    assert_eq!(limits.seeding_interval, 1440);
+
+    let update = TaskLimitsUpdate::new()
+        .max_upload_rate(256)
+        .seeding_ratio(2.0);
+    synods.set_task_limits("task_id_1", &update).await.unwrap();
+
+    for update in [
+        TaskLimitsUpdate::new(),
+        TaskLimitsUpdate::new().seeding_ratio(-1.0),
+    ] {
+        let error = synods
+            .set_task_limits("task_id_1", &update)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SynoError>(),
+            Some(SynoError::InvalidInput(_))
+        ));
+    }
 }
 
 #[tokio::test]
-async fn test_clear_completed() {
+async fn test_get_and_update_settings() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    // The finished status is 5 (TaskStatus::Finished)
     let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "delete_condition"),
-        ("status", "5"),
+        ("api", "SYNO.DownloadStation2.Settings.BT"),
+        ("version", "1"),
+        ("method", "get"),
     ];
-
     create_api_mock(
         &mut server,
         params,
-        "test-files/clear_completed_success.json",
+        "test-files/get_bt_settings_success.json",
     )
     .await;
 
-    let result = synods.clear_completed().await;
+    let bt = synods.get_settings::<BtSettings>().await.unwrap();
+    assert_eq!(bt.tcp_port, 16881);
+    assert!(bt.enable_dht);
+    assert_eq!(bt.max_peers, 100);
+    assert_eq!(bt.max_upload_rate, 1024);
 
-    server.verify().await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Settings.Global"),
+        ("method", "set"),
+        ("max_concurrent_download", "5"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    // Verify the operation was successful
-    assert!(result.is_ok());
+    synods
+        .update_settings(&GlobalSettingsUpdate {
+            default_destination: None,
+            max_concurrent_download: Some(5),
+        })
+        .await
+        .unwrap();
+
+    // Only the fields set in the update are sent
+    let requests = server.received_requests().await.unwrap();
+    let body = String::from_utf8_lossy(&requests.last().unwrap().body).into_owned();
+    assert!(body.contains("max_concurrent_download=5"));
+    assert!(!body.contains("default_destination"));
+
+    let error = synods
+        .update_settings(&BtSettingsUpdate::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
 }
 
 #[tokio::test]
-async fn test_session_expired_auto_retry() {
+async fn test_scheduler_settings() {
     let (mut server, synods) = setup_client().await;
 
-    // Initial login
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    server.reset().await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Settings.Scheduler"),
+        ("method", "get"),
+    ];
+    create_api_mock(&mut server, params, "test-files/get_scheduler_success.json").await;
+
+    let scheduler = synods.get_settings::<SchedulerSettings>().await.unwrap();
+    assert!(scheduler.enabled);
+    assert_eq!(scheduler.alt_download_rate, 100);
+    assert_eq!(
+        scheduler.schedule.get(Weekday::Sun, 7),
+        Some(SpeedMode::Off)
+    );
+    assert_eq!(
+        scheduler.schedule.get(Weekday::Mon, 8),
+        Some(SpeedMode::Normal)
+    );
+    assert_eq!(
+        scheduler.schedule.get(Weekday::Fri, 9),
+        Some(SpeedMode::Alternative)
+    );
+
+    let mut expected = WeeklySchedule::new(SpeedMode::Normal);
+    expected
+        .set_hours(WeeklySchedule::WEEKDAYS, 9..18, SpeedMode::Alternative)
+        .unwrap();
+    expected
+        .set_hours(WeeklySchedule::WEEKEND, 0..8, SpeedMode::Off)
+        .unwrap();
+    assert_eq!(scheduler.schedule, expected);
+
+    // Copy the schedule, e.g. to another NAS
+    let schedule = format!("\"{}\"", scheduler.schedule);
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Settings.Scheduler"),
+        ("method", "set"),
+        ("enabled", "true"),
+        ("schedule", &schedule),
+        ("alt_download_rate", "100"),
+        ("alt_upload_rate", "20"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    synods
+        .update_settings(&SchedulerSettingsUpdate::from(scheduler))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_rss_sites() {
+    let (mut server, synods) = setup_client().await;
 
-    // Re-mount login mock for re-auth
     create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
 
-    // Mount expired response (only matches once)
-    Mock::given(method("POST"))
-        .and(path("/webapi/entry.cgi"))
-        .and(header("content-type", "application/x-www-form-urlencoded"))
-        .and(form_param("api", "SYNO.DownloadStation2.Task"))
-        .and(form_param("method", "list"))
-        .and(form_param("_sid", "456"))
-        .respond_with(
-            ResponseTemplate::new(200)
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.RSS.Site"),
+        ("method", "list"),
+    ];
+    create_api_mock(&mut server, params, "test-files/get_rss_sites_success.json").await;
+
+    for (method, param, value) in [
+        ("create", "url", "\"https://example.com/rss\""),
+        ("refresh", "id", "1"),
+        ("delete", "id", "1"),
+    ] {
+        let params = vec![
+            ("api", "SYNO.DownloadStation2.RSS.Site"),
+            ("method", method),
+            (param, value),
+        ];
+        create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+    }
+
+    let sites = synods.get_rss_sites().await.unwrap();
+    assert_eq!(sites.total, 1);
+    assert_eq!(sites.site[0].id, 1);
+    assert_eq!(sites.site[0].title, "Weekly Releases");
+    assert_eq!(sites.site[0].last_update.timestamp(), 1_609_459_200);
+
+    synods
+        .add_rss_site("https://example.com/rss")
+        .await
+        .unwrap();
+    synods.refresh_rss_site(1).await.unwrap();
+    synods.delete_rss_site(1).await.unwrap();
+
+    let error = synods
+        .add_rss_site("ftp://example.com/rss")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_rss_items_and_download() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.RSS.Item"),
+        ("method", "list"),
+        ("rss_id", "1"),
+    ];
+    create_api_mock(&mut server, params, "test-files/get_rss_items_success.json").await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "create"),
+        ("url", "magnet:?xt=urn:btih:episode2"),
+        ("destination", "downloads/shows"),
+    ];
+    create_api_mock(&mut server, params, "test-files/create_task_success.json").await;
+
+    let items = synods.get_rss_items(1).await.unwrap();
+    assert_eq!(items.total, 2);
+    assert!(items.item[0].is_new);
+    assert_eq!(items.item[0].size, 734_003_200);
+    assert!(!items.item[1].is_new);
+
+    let created = synods
+        .create_task_from_rss_item(&items.item[0], "downloads/shows")
+        .await
+        .unwrap();
+    assert_eq!(created.task_id, ["task_id_3"]);
+}
+
+#[tokio::test]
+async fn test_rss_filters() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.RSS.Filter"),
+        ("method", "list"),
+        ("rss_id", "1"),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/get_rss_filters_success.json",
+    )
+    .await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.RSS.Filter"),
+        ("method", "set"),
+        ("id", "7"),
+        ("rss_id", "1"),
+        ("name", "\"Show\""),
+        ("match", "\"show 1080p\""),
+        ("not_match", "\"sample\""),
+        ("destination", "\"downloads/shows\""),
+        ("enabled", "false"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.RSS.Filter"),
+        ("method", "delete"),
+        ("id", "7"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    let filters = synods.get_rss_filters(1).await.unwrap();
+    assert_eq!(filters.total, 1);
+    let mut filter = filters.filter[0].clone();
+    assert_eq!(filter.match_pattern, "show");
+    assert_eq!(filter.not_match_pattern, "sample");
+    assert!(filter.enabled);
+
+    filter.match_pattern = "show 1080p".into();
+    filter.enabled = false;
+    synods.update_rss_filter(&filter).await.unwrap();
+    synods.delete_rss_filter(7).await.unwrap();
+
+    filter.name = String::new();
+    let error = synods.add_rss_filter(&filter).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+
+    let new_filter = RssFilter {
+        rss_id: 1,
+        name: "New filter".into(),
+        destination: "downloads".into(),
+        ..Default::default()
+    };
+    let error = synods.update_rss_filter(&new_filter).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_dry_run_rss_filter() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.RSS.Item"),
+        ("method", "list"),
+        ("rss_id", "1"),
+    ];
+    create_api_mock(&mut server, params, "test-files/get_rss_items_success.json").await;
+
+    let filter = RssFilter {
+        rss_id: 1,
+        name: "Show".into(),
+        match_pattern: "SHOW s01".into(),
+        not_match_pattern: "e01".into(),
+        destination: "downloads/shows".into(),
+        ..Default::default()
+    };
+
+    let matched = synods.dry_run_rss_filter(&filter).await.unwrap();
+
+    assert_eq!(matched.len(), 1);
+    assert_eq!(matched[0].title, "Show S01E02");
+}
+
+#[tokio::test]
+async fn test_bt_search() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "start"),
+        ("keyword", "\"ubuntu 24.04\""),
+        ("module", "\"enabled\""),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/start_bt_search_success.json",
+    )
+    .await;
+
+    // The first poll reports a running search, later polls the results
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("api", "SYNO.DownloadStation2.BTSearch"))
+        .and(form_param("method", "list"))
+        .and(form_param("id", "\"search_1\""))
+        .respond_with(
+            ResponseTemplate::new(200)
                 .append_header("content-type", "application/json")
-                .set_body_string(body_from_file("test-files/session_expired.json")),
+                .set_body_string(body_from_file("test-files/bt_search_running.json")),
         )
         .up_to_n_times(1)
+        .with_priority(1)
         .mount(&server)
         .await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "list"),
+        ("id", "\"search_1\""),
+        ("offset", "0"),
+        ("limit", "-1"),
+    ];
+    create_api_mock(&mut server, params, "test-files/bt_search_finished.json").await;
 
-    // Mount success response for retry (matches after expired mock is exhausted)
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "create"),
+        ("url", "magnet:?xt=urn:btih:ubuntu2404"),
+        ("destination", "downloads"),
+    ];
+    create_api_mock(&mut server, params, "test-files/create_task_success.json").await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "clean"),
+        ("id", "\"search_1\""),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    let search = synods
+        .start_bt_search("ubuntu 24.04")
+        .await
+        .unwrap()
+        .poll_interval(Duration::from_millis(10));
+    assert_eq!(search.id(), "search_1");
+
+    let results = search.wait().await.unwrap();
+    assert!(results.finished);
+    assert_eq!(results.total, 2);
+    assert_eq!(results.items[0].seeds, 512);
+    assert_eq!(results.items[0].size, 6_114_656_256);
+    assert_eq!(results.items[1].external_link, "");
+
+    let created = search
+        .add_result(&results.items[0], "downloads")
+        .await
+        .unwrap();
+    assert_eq!(created.task_id, ["task_id_3"]);
+    search.clean().await.unwrap();
+
+    let Err(error) = synods.start_bt_search(" ").await else {
+        panic!("expected an empty keyword to be rejected");
+    };
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_bt_search_wait_times_out() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "start"),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/start_bt_search_success.json",
+    )
+    .await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "list"),
+    ];
+    create_api_mock(&mut server, params, "test-files/bt_search_running.json").await;
+
+    let search = synods
+        .start_bt_search("ubuntu 24.04")
+        .await
+        .unwrap()
+        .poll_interval(Duration::from_millis(10))
+        .timeout(Duration::from_millis(100));
+
+    let error = search.wait().await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::Timeout(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_bt_search_categories_and_modules() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "list_category"),
+    ];
+    create_api_mock(&mut server, params, "test-files/bt_search_categories.json").await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.BTSearch"),
+        ("method", "list_module"),
+    ];
+    create_api_mock(&mut server, params, "test-files/bt_search_modules.json").await;
+
+    let categories = synods.get_bt_search_categories().await.unwrap();
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[1].id, "software");
+
+    let modules = synods.get_bt_search_modules().await.unwrap();
+    assert_eq!(modules.len(), 2);
+    assert!(modules[0].enabled);
+    assert!(!modules[1].enabled);
+}
+
+#[tokio::test]
+async fn test_get_package_info() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Package.Info"),
+        ("version", "2"),
+        ("method", "get"),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/get_package_info_success.json",
+    )
+    .await;
+
+    let info = synods.get_package_info().await.unwrap();
+    assert_eq!(info.version, 4680);
+    assert_eq!(info.version_string, "4.0.1-4680");
+    assert!(info.is_manager);
+    assert_eq!(info.default_destination, "downloads");
+}
+
+// Helper function to create a mock reporting the Download Station package as stopped
+async fn create_service_stopped_mock(server: &mut MockServer) {
+    let params = vec![
+        ("api", "SYNO.Core.Package"),
+        ("method", "get"),
+        ("id", "DownloadStation"),
+    ];
+    create_api_mock(server, params, "test-files/get_service_status_stopped.json").await;
+}
+
+#[tokio::test]
+async fn test_missing_api_with_stopped_service() {
+    let (mut server, synods) =
+        setup_client_with_api_info("test-files/api_info_download_station_stopped.json").await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+    create_service_stopped_mock(&mut server).await;
+
+    let error = synods.pause("task_id_1").await.unwrap_err();
+    match error.downcast_ref::<SynoError>() {
+        Some(SynoError::ServiceNotRunning { api }) => {
+            assert_eq!(api, "SYNO.DownloadStation2.Task");
+        }
+        other => panic!("Expected SynoError::ServiceNotRunning, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_api_not_found_with_stopped_service() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+    create_service_stopped_mock(&mut server).await;
+    let params = vec![("api", "SYNO.DownloadStation2.Task"), ("method", "pause")];
+    create_api_mock(&mut server, params, "test-files/api_not_found_error.json").await;
+
+    let error = synods.pause("task_id_1").await.unwrap_err();
+    match error.downcast_ref::<SynoError>() {
+        Some(SynoError::ServiceNotRunning { api }) => {
+            assert_eq!(api, "SYNO.DownloadStation2.Task");
+        }
+        other => panic!("Expected SynoError::ServiceNotRunning, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_service_status_and_control() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.Core.Package"),
+        ("method", "get"),
+        ("id", "DownloadStation"),
+        ("additional", "[\"status\"]"),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/get_service_status_stopped.json",
+    )
+    .await;
+
+    let params = vec![
+        ("api", "SYNO.Core.Package.Control"),
+        ("method", "start"),
+        ("id", "DownloadStation"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    let params = vec![
+        ("api", "SYNO.Core.Package.Control"),
+        ("method", "stop"),
+        ("id", "DownloadStation"),
+    ];
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let status = synods.get_service_status().await.unwrap();
+    assert_eq!(status, ServiceStatus::Stopped);
+    assert!(!status.is_running());
+
+    synods.start_service().await.unwrap();
+
+    let error = synods.stop_service().await.unwrap_err();
+    let syno_err = error
+        .downcast_ref::<SynoError>()
+        .expect("expected SynoError");
+    assert!(matches!(syno_err, SynoError::Api { code: 403, .. }));
+    // 403 is not a Download Station destination error for the Core package APIs
+    assert_eq!(syno_err.error_code(), Some(ErrorCode::Other(403)));
+}
+
+#[tokio::test]
+async fn test_get_statistics() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.Statistic"),
+        ("method", "get"),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/get_statistics_success.json",
+    )
+    .await;
+
+    let statistics = synods.get_statistics().await.unwrap();
+    assert_eq!(statistics.speed_download, 5_242_880);
+    assert_eq!(statistics.speed_upload, 1_048_576);
+    assert_eq!(statistics.emule_speed_download, None);
+    assert_eq!(statistics.emule_speed_upload, None);
+}
+
+#[tokio::test]
+async fn test_watch_tasks() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    // The first poll fails, the second records the tasks and later polls see the changes
+    Mock::given(method("POST"))
+        .and(form_param("api", "SYNO.DownloadStation2.Task"))
+        .and(form_param("method", "list"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
     Mock::given(method("POST"))
-        .and(path("/webapi/entry.cgi"))
-        .and(header("content-type", "application/x-www-form-urlencoded"))
         .and(form_param("api", "SYNO.DownloadStation2.Task"))
         .and(form_param("method", "list"))
-        .and(form_param("_sid", "456"))
         .respond_with(
             ResponseTemplate::new(200)
                 .append_header("content-type", "application/json")
-                .set_body_string(body_from_file("test-files/get_tasks_success.json")),
+                .set_body_string(body_from_file("test-files/watch_tasks_1.json")),
+        )
+        .up_to_n_times(1)
+        .with_priority(2)
+        .mount(&server)
+        .await;
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "list"),
+        ("additional", "[\"transfer\"]"),
+    ];
+    create_api_mock(&mut server, params, "test-files/watch_tasks_2.json").await;
+
+    let mut events = synods
+        .watch_tasks()
+        .interval(Duration::from_millis(10))
+        .max_backoff(Duration::from_millis(20))
+        .milestones(&[50, 25])
+        .into_stream();
+
+    assert!(events.next().await.unwrap().is_err());
+
+    let mut received = Vec::new();
+    for _ in 0..8 {
+        received.push(events.next().await.unwrap().unwrap());
+    }
+
+    assert!(matches!(
+        &received[0],
+        TaskEvent::StatusChanged { task, from: TaskStatus::Downloading, to: TaskStatus::Finished }
+            if task.id == "task_id_1"
+    ));
+    assert!(matches!(&received[1], TaskEvent::Completed(task) if task.id == "task_id_1"));
+    assert!(matches!(
+        &received[2],
+        TaskEvent::ProgressMilestone { task, percent: 25 } if task.id == "task_id_1"
+    ));
+    assert!(matches!(
+        &received[3],
+        TaskEvent::ProgressMilestone { percent: 50, .. }
+    ));
+    assert!(matches!(
+        &received[4],
+        TaskEvent::StatusChanged {
+            to: TaskStatus::ErrorBrokenLink,
+            ..
+        }
+    ));
+    assert!(matches!(
+        &received[5],
+        TaskEvent::Errored { task, error_detail: Some(detail) }
+            if task.id == "task_id_2" && detail == "broken_link"
+    ));
+    assert!(matches!(&received[6], TaskEvent::TaskAdded(task) if task.id == "task_id_3"));
+    assert!(matches!(&received[7], TaskEvent::TaskRemoved(task) if task.id == "task_id_4"));
+}
+
+#[tokio::test]
+async fn test_watch_tasks_walks_pages() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![("method", "list"), ("offset", "0"), ("limit", "1")];
+    create_api_mock(&mut server, params, "test-files/watch_tasks_page_1.json").await;
+    // The second page changes after the first poll
+    Mock::given(method("POST"))
+        .and(form_param("method", "list"))
+        .and(form_param("offset", "1"))
+        .and(form_param("limit", "1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/watch_tasks_page_2.json")),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    let params = vec![("method", "list"), ("offset", "1"), ("limit", "1")];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/watch_tasks_page_2_changed.json",
+    )
+    .await;
+
+    let mut watcher = synods
+        .watch_tasks()
+        .interval(Duration::from_millis(10))
+        .page_size(1);
+
+    let added = watcher.next_event().await.unwrap();
+    assert!(matches!(added, TaskEvent::TaskAdded(task) if task.id == "task_id_3"));
+    let removed = watcher.next_event().await.unwrap();
+    assert!(matches!(removed, TaskEvent::TaskRemoved(task) if task.id == "task_id_2"));
+}
+
+// Helper function to mock polls of a task, answering the first polls with the given file
+async fn create_task_poll_mock(server: &MockServer, response_file: &str, times: Option<u64>) {
+    let mut mock = Mock::given(method("POST"))
+        .and(form_param("api", "SYNO.DownloadStation2.Task"))
+        .and(form_param("method", "get"))
+        .and(form_param("id", "task_id_1"))
+        .and(form_param("additional", "[\"transfer\"]"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file(response_file)),
+        );
+    if let Some(times) = times {
+        mock = mock.up_to_n_times(times).with_priority(1);
+    }
+    mock.mount(server).await;
+}
+
+#[tokio::test]
+async fn test_wait_for_completion() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    create_task_poll_mock(&server, "test-files/wait_task_downloading.json", Some(2)).await;
+    create_task_poll_mock(&server, "test-files/wait_task_seeding.json", None).await;
+
+    let task = synods
+        .wait_for_completion("task_id_1")
+        .min_interval(Duration::from_millis(10))
+        .max_interval(Duration::from_millis(20))
+        .timeout(Duration::from_secs(5))
+        .wait()
+        .await
+        .unwrap();
+
+    assert_eq!(task.status, TaskStatus::Seeding);
+    assert_eq!(server.received_requests().await.unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_task_failed() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    create_task_poll_mock(&server, "test-files/wait_task_failed.json", None).await;
+
+    let error = synods
+        .wait_for_completion("task_id_1")
+        .wait()
+        .await
+        .unwrap_err();
+
+    let syno_error = error.downcast_ref::<SynoError>().unwrap();
+    assert!(matches!(
+        syno_error,
+        SynoError::TaskFailed { task_id, error_detail: Some(detail), .. }
+            if task_id == "task_id_1" && detail == "disk_full"
+    ));
+    assert_eq!(syno_error.task_status(), Some(TaskStatus::ErrorDiskFull));
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_retries_failed_polls() {
+    // Reserve a free port and close it, so the first polls find the NAS unreachable
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    drop(listener);
+
+    let synods = SynoDS::builder()
+        .url(format!("http://{address}"))
+        .username("test")
+        .password("test123")
+        .build()
+        .unwrap();
+
+    let waiting = synods
+        .wait_for_completion("task_id_1")
+        .min_interval(Duration::from_millis(10))
+        .max_interval(Duration::from_millis(100))
+        .timeout(Duration::from_secs(5))
+        .wait();
+
+    // The NAS comes back, failing the next poll with a server error before the task is done
+    let start_server = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let listener = std::net::TcpListener::bind(address).unwrap();
+        let server = MockServer::builder().listener(listener).start().await;
+        create_api_info_mock(&server, "test-files/api_info_success.json").await;
+        Mock::given(method("POST"))
+            .and(form_param("api", "SYNO.DownloadStation2.Task"))
+            .and(form_param("method", "get"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        create_task_poll_mock(&server, "test-files/wait_task_seeding.json", None).await;
+        server
+    };
+
+    let (task, server) = tokio::join!(waiting, start_server);
+
+    assert_eq!(task.unwrap().status, TaskStatus::Seeding);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_task_not_found() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    create_task_poll_mock(&server, "test-files/wait_task_missing.json", None).await;
+
+    let error = synods
+        .wait_for_completion("task_id_1")
+        .timeout(Duration::from_secs(5))
+        .wait()
+        .await
+        .unwrap_err();
+    let syno_error = error.downcast_ref::<SynoError>().unwrap();
+    assert!(matches!(syno_error, SynoError::TaskNotFound(task_id) if task_id == "task_id_1"));
+    assert_eq!(syno_error.error_code(), None);
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_invalid_task_id() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    create_task_poll_mock(&server, "test-files/invalid_task_id_error.json", None).await;
+
+    let error = synods
+        .wait_for_completion("task_id_1")
+        .timeout(Duration::from_secs(5))
+        .wait()
+        .await
+        .unwrap_err();
+    let syno_error = error.downcast_ref::<SynoError>().unwrap();
+    assert!(matches!(syno_error, SynoError::TaskNotFound(task_id) if task_id == "task_id_1"));
+}
+
+#[tokio::test]
+async fn test_wait_for_completion_timeout_and_cancel() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    create_task_poll_mock(&server, "test-files/wait_task_downloading.json", None).await;
+
+    let error = synods
+        .wait_for_completion("task_id_1")
+        .min_interval(Duration::from_millis(10))
+        .timeout(Duration::from_millis(50))
+        .wait()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::Timeout(_))
+    ));
+
+    let (cancel, cancelled) = tokio::sync::oneshot::channel::<()>();
+    // A timeout too large for an instant means no deadline
+    let waiting = synods
+        .wait_for_completion("task_id_1")
+        .min_interval(Duration::from_millis(10))
+        .timeout(Duration::MAX)
+        .cancel_on(async {
+            cancelled.await.ok();
+        })
+        .wait();
+    cancel.send(()).unwrap();
+    let error = waiting.await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::Cancelled(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_create_task() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let uri = "https://example.com/test.iso";
+    let destination = "/downloads";
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "create"),
+        ("type", "\"url\""),
+        ("destination", destination),
+        ("url", uri),
+        ("create_list", "false"),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/create_task_success.json").await;
+
+    let created = synods.create_task(uri, destination).await.unwrap();
+
+    server.verify().await;
+
+    // Verify the created task is reported
+    assert_eq!(created.task_id, ["task_id_3"]);
+    assert!(created.list_id.is_empty());
+}
+
+#[tokio::test]
+async fn test_create_task_list_flow() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let uri = "magnet:?xt=urn:btih:test";
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "create"),
+        ("url", uri),
+        ("create_list", "true"),
+        ("extract_password", "\"secret\""),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/create_task_list_success.json",
+    )
+    .await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.List"),
+        ("method", "get"),
+        ("list_id", "\"list_id_1\""),
+    ];
+    create_api_mock(&mut server, params, "test-files/get_task_list_success.json").await;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.List.Polling"),
+        ("method", "download"),
+        ("list_id", "\"list_id_1\""),
+        ("file_indexes", "[0,2]"),
+        ("destination", "\"downloads\""),
+        ("create_subfolder", "false"),
+        ("extract_password", "\"secret\""),
+    ];
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/confirm_task_list_success.json",
+    )
+    .await;
+
+    let options = CreateTaskOptions::new()
+        .create_list(true)
+        .extract_password("secret");
+    let created = synods
+        .create_task_with(uri, "downloads", &options)
+        .await
+        .unwrap();
+    assert_eq!(created.list_id, ["list_id_1"]);
+    assert!(created.task_id.is_empty());
+
+    let list = synods.get_task_list(&created.list_id[0]).await.unwrap();
+    assert_eq!(list.title, "Test Torrent");
+    assert_eq!(list.files.len(), 3);
+    assert_eq!(list.files[1].name, "sample.mkv");
+
+    let wanted = list
+        .files
+        .iter()
+        .filter(|file| !file.name.starts_with("sample"))
+        .map(|file| file.index)
+        .collect::<Vec<_>>();
+    let created = synods
+        .confirm_task_list(
+            "list_id_1",
+            &wanted,
+            "downloads",
+            &options.create_subfolder(false),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(created.task_id, ["task_id_4"]);
+    assert_eq!(created.list_id, ["list_id_1"]);
+}
+
+#[tokio::test]
+async fn test_confirm_task_list_requires_files() {
+    let (_server, synods) = setup_client().await;
+
+    let error = synods
+        .confirm_task_list("list_id_1", &[], "downloads", &CreateTaskOptions::new())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_create_task_from_file() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let file_path = "test-files/test.torrent";
+    let file_name = "test.torrent";
+    let destination = "/downloads";
+
+    // Read the test file
+    let file_data = fs::read(file_path).expect("Failed to read test file");
+
+    // Create a mock for the file upload
+    create_file_upload_mock(&mut server, "test-files/create_task_from_file_success.json").await;
+
+    let result = synods
+        .create_task_from_file(&file_data, file_name, destination)
+        .await;
+
+    server.verify().await;
+
+    // Verify the operation was successful
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_pause() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let task_id = "task_id_1";
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "pause"),
+        ("id", task_id),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    let result = synods.pause(task_id).await;
+
+    server.verify().await;
+
+    // Verify the operation was successful
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_resume() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let task_id = "task_id_1";
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "resume"),
+        ("id", task_id),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/resume_success.json").await;
+
+    let result = synods.resume(task_id).await;
+
+    server.verify().await;
+
+    // Verify the operation was successful
+    assert!(result.is_ok());
+
+    // Verify the response data
+    assert_eq!(result.unwrap().failed_task.len(), 0);
+}
+
+#[tokio::test]
+async fn test_complete() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let task_id = "task_id_1";
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task.Complete"),
+        ("version", "1"),
+        ("method", "start"),
+        ("id", task_id),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/complete_success.json").await;
+
+    let result = synods.complete(task_id).await;
+
+    server.verify().await;
+
+    // Verify the operation was successful
+    assert!(result.is_ok());
+
+    // Verify the response data
+    let task_completed = result.unwrap();
+    assert_eq!(task_completed.task_id, "task_id_1");
+}
+
+#[tokio::test]
+async fn test_delete_task() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let task_id = "task_id_1";
+    let force_complete = true;
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "delete"),
+        ("id", task_id),
+        ("force_complete", "true"),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/delete_task_success.json").await;
+
+    let result = synods.delete_task(task_id, force_complete).await;
+
+    server.verify().await;
+
+    // Verify the operation was successful
+    assert!(result.is_ok());
+
+    // Verify the response data
+    let task_operation = result.unwrap();
+    assert_eq!(task_operation.failed_task.len(), 0);
+}
+
+#[tokio::test]
+async fn test_clear_completed() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    // The finished status is 5 (TaskStatus::Finished)
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "delete_condition"),
+        ("status", "5"),
+    ];
+
+    create_api_mock(
+        &mut server,
+        params,
+        "test-files/clear_completed_success.json",
+    )
+    .await;
+
+    let result = synods.clear_completed().await;
+
+    server.verify().await;
+
+    // Verify the operation was successful
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_session_expired_auto_retry() {
+    let (mut server, synods) = setup_client().await;
+
+    // Initial login
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    server.reset().await;
+
+    // Re-mount login mock for re-auth
+    create_login_mock(&mut server).await;
+
+    // Mount expired response (only matches once)
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(form_param("api", "SYNO.DownloadStation2.Task"))
+        .and(form_param("method", "list"))
+        .and(form_param("_sid", "456"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/session_expired.json")),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    // Mount success response for retry (matches after expired mock is exhausted)
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(form_param("api", "SYNO.DownloadStation2.Task"))
+        .and(form_param("method", "list"))
+        .and(form_param("_sid", "456"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/get_tasks_success.json")),
+        )
+        .mount(&server)
+        .await;
+
+    // Re-authorize after server reset
+    synods.authorize().await.unwrap();
+
+    let tasks = synods.get_tasks().await.unwrap();
+    assert_eq!(tasks.total, 2);
+}
+
+#[tokio::test]
+async fn test_session_expired_reauth_fails() {
+    let (mut server, synods) = setup_client().await;
+
+    // Initial login
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    server.reset().await;
+
+    // First call returns session expired
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(form_param("api", "SYNO.DownloadStation2.Task"))
+        .and(form_param("method", "list"))
+        .and(form_param("_sid", "456"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/session_expired.json")),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    // Re-auth fails (returns failure)
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(r#"{"success": false}"#),
+        )
+        .mount(&server)
+        .await;
+
+    let result = synods.get_tasks().await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_api_error_preserves_code() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "list"),
+        (
+            "additional",
+            r#"["transfer","tracker","peer","file","detail"]"#,
+        ),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let result = synods.get_tasks().await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::Api { code, .. } => assert_eq!(*code, 403),
+        other => panic!("Expected SynoError::Api, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_session_expired_file_upload_retry() {
+    let (mut server, synods) = setup_client().await;
+
+    // Initial login
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    server.reset().await;
+
+    // Re-mount login mock for re-auth
+    create_login_mock(&mut server).await;
+
+    // First file upload returns session expired
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header_regex("content-type", "multipart/form-data"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/session_expired.json")),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+
+    // Retry succeeds
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header_regex("content-type", "multipart/form-data"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file(
+                    "test-files/create_task_from_file_success.json",
+                )),
+        )
+        .mount(&server)
+        .await;
+
+    let file_data = fs::read("test-files/test.torrent").expect("Failed to read test file");
+    let result = synods
+        .create_task_from_file(&file_data, "test.torrent", "/downloads")
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_auth_error_preserves_code() {
+    let (server, synods) = setup_client().await;
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header("content-type", "application/x-www-form-urlencoded"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/auth_error.json")),
+        )
+        .mount(&server)
+        .await;
+
+    let result = synods.authorize().await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::Auth {
+            code: Some(400), ..
+        } => {}
+        other => panic!("Expected SynoError::Auth with code 400, got: {other:?}"),
+    }
+    assert_eq!(syno_err.error_code(), Some(ErrorCode::IncorrectCredentials));
+}
+
+#[tokio::test]
+async fn test_create_task_error_preserves_code() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "create"),
+        ("type", "\"url\""),
+        ("destination", "/downloads"),
+        ("url", "https://example.com/test.iso"),
+        ("create_list", "false"),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let result = synods
+        .create_task("https://example.com/test.iso", "/downloads")
+        .await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::Api { code: 403, .. } => {}
+        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_resume_error_preserves_code() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "resume"),
+        ("id", "task_id_1"),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let result = synods.resume("task_id_1").await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::Api { code: 403, .. } => {}
+        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_delete_task_error_preserves_code() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "delete"),
+        ("id", "task_id_1"),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let result = synods.delete_task("task_id_1", false).await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::Api { code: 403, .. } => {}
+        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_clear_completed_error_preserves_code() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "2"),
+        ("method", "delete_condition"),
+        ("status", "5"),
+    ];
+
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let result = synods.clear_completed().await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::Api { code: 403, .. } => {}
+        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_api_info_is_queried_once() {
+    let server = MockServer::start().await;
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .build()
+        .unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/query.cgi"))
+        .and(form_param("api", "SYNO.API.Info"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/api_info_success.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let api_info = synods.api_info().await.unwrap();
+    assert_eq!(api_info["SYNO.DownloadStation2.Task"].max_version, 2);
+    assert_eq!(api_info["SYNO.DownloadStation2.Task"].path, "entry.cgi");
+
+    synods.api_info().await.unwrap();
+
+    server.verify().await;
+}
+
+// Helper function to create a mock answering the API info query with the given file once,
+// before the mocks created by `create_api_info_mock`
+async fn create_api_info_mock_once(server: &MockServer, response_file: &str) {
+    Mock::given(method("POST"))
+        .and(path("/webapi/query.cgi"))
+        .and(form_param("api", "SYNO.API.Info"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file(response_file)),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_api_info_is_refreshed_when_api_is_missing() {
+    let (mut server, synods) = setup_client().await;
+    // Download Station is installed after the API info was first queried
+    create_api_info_mock_once(&server, "test-files/api_info_without_download_station.json").await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+    assert!(
+        !synods
+            .api_info()
+            .await
+            .unwrap()
+            .contains_key("SYNO.DownloadStation2.Task")
+    );
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("method", "pause"),
+        ("id", "task_id_1"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    synods.pause("task_id_1").await.unwrap();
+
+    assert!(
+        synods
+            .api_info()
+            .await
+            .unwrap()
+            .contains_key("SYNO.DownloadStation2.Task")
+    );
+    server.verify().await;
+    let info_requests = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/webapi/query.cgi")
+        .count();
+    assert_eq!(info_requests, 2);
+}
+
+#[tokio::test]
+async fn test_api_info_is_refreshed_on_api_not_found() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![("method", "pause"), ("id", "task_id_1")];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("method", "pause"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/api_not_found_error.json")),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    synods.pause("task_id_1").await.unwrap();
+
+    let info_requests = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/webapi/query.cgi")
+        .count();
+    assert_eq!(info_requests, 2);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_refresh_api_info() {
+    let (server, synods) = setup_client().await;
+    create_api_info_mock_once(&server, "test-files/api_info_without_download_station.json").await;
+
+    let api_info = synods.api_info().await.unwrap();
+    assert!(!api_info.contains_key("SYNO.DownloadStation2.Task"));
+
+    let api_info = synods.refresh_api_info().await.unwrap();
+    assert!(api_info.contains_key("SYNO.DownloadStation2.Task"));
+    assert!(
+        synods
+            .api_info()
+            .await
+            .unwrap()
+            .contains_key("SYNO.DownloadStation2.Task")
+    );
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_version_negotiation_uses_highest_common_version() {
+    let (mut server, synods) =
+        setup_client_with_api_info("test-files/api_info_legacy_versions.json").await;
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("version", "6"))
+        .and(form_param("method", "login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/login_success.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    synods.authorize().await.unwrap();
+
+    let params = vec![
+        ("api", "SYNO.DownloadStation2.Task"),
+        ("version", "1"),
+        ("method", "pause"),
+        ("id", "task_id_1"),
+    ];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    synods.pause("task_id_1").await.unwrap();
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_api_not_available() {
+    let (mut server, synods) =
+        setup_client_with_api_info("test-files/api_info_legacy_versions.json").await;
+
+    create_login_mock(&mut server).await;
+
+    let result = synods.complete("task_id_1").await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    match syno_err {
+        SynoError::ApiNotAvailable { api, .. } => {
+            assert_eq!(api, "SYNO.DownloadStation2.Task.Complete");
+        }
+        other => panic!("Expected SynoError::ApiNotAvailable, got: {other:?}"),
+    }
+}
+
+// Helper function to create a client talking to a NAS with only the legacy Download Station API
+async fn setup_legacy_client() -> (MockServer, SynoDS) {
+    let (server, synods) = setup_client_with_api_info("test-files/api_info_dsm6.json").await;
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/auth.cgi"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("version", "6"))
+        .and(form_param("method", "login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/login_success.json")),
+        )
+        .mount(&server)
+        .await;
+    synods.authorize().await.unwrap();
+
+    (server, synods)
+}
+
+// Helper function to create a mock for any legacy Download Station API call
+async fn create_legacy_api_mock(
+    server: &MockServer,
+    params: Vec<(&str, &str)>,
+    response_file: &str,
+) {
+    let mut builder = Mock::given(method("POST"))
+        .and(path("/webapi/DownloadStation/task.cgi"))
+        .and(form_param("api", "SYNO.DownloadStation.Task"))
+        .and(form_param("version", "3"));
+    for (key, value) in params {
+        builder = builder.and(form_param(key, value));
+    }
+    builder
+        .and(form_param("_sid", "456"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file(response_file)),
+        )
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_legacy_backend_detected() {
+    let (_server, synods) = setup_legacy_client().await;
+
+    assert_eq!(synods.backend().await.unwrap(), Backend::Legacy);
+}
+
+#[tokio::test]
+async fn test_legacy_get_tasks() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![
+        ("method", "list"),
+        ("offset", "0"),
+        ("limit", "-1"),
+        ("additional", "transfer,tracker,peer,file,detail"),
+    ];
+    create_legacy_api_mock(&server, params, "test-files/legacy_get_tasks_success.json").await;
+
+    let tasks = synods.get_tasks().await.unwrap();
+
+    server.verify().await;
+
+    assert_eq!(tasks.total, 2);
+    assert_eq!(tasks.task[0].id, "dbid_1");
+    assert!(matches!(tasks.task[0].status, TaskStatus::Finished));
+    assert!(matches!(tasks.task[1].status, TaskStatus::ErrorBrokenLink));
+
+    let additional = tasks.task[0].additional.as_ref().unwrap();
+    let detail = additional.detail.as_ref().unwrap();
+    assert_eq!(detail.destination, "downloads");
+    assert_eq!(detail.created_time.timestamp(), 1_609_459_100);
+    let files = additional.file.as_ref().unwrap();
+    assert_eq!(files[0].index, 0);
+    assert!(files[0].wanted);
+    assert_eq!(files[0].priority, FilePriority::Normal);
+    assert_eq!(
+        additional.transfer.as_ref().unwrap().size_uploaded,
+        536_870_912
+    );
+}
+
+#[tokio::test]
+async fn test_legacy_get_tasks_unknown_status() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![
+        ("method", "list"),
+        ("offset", "0"),
+        ("limit", "-1"),
+        ("additional", "transfer,tracker,peer,file,detail"),
+    ];
+    create_legacy_api_mock(
+        &server,
+        params,
+        "test-files/legacy_get_tasks_unknown_status.json",
+    )
+    .await;
+
+    let tasks = synods.get_tasks().await.unwrap();
+
+    server.verify().await;
+
+    assert_eq!(tasks.task.len(), 1);
+    assert_eq!(tasks.task[0].status, TaskStatus::Unknown(0));
+    assert!(!tasks.task[0].status.is_error());
+    assert_eq!(
+        tasks.task[0]
+            .status_extra
+            .as_ref()
+            .and_then(|extra| extra.status_name.as_deref()),
+        Some("captcha_needed")
+    );
+}
+
+#[tokio::test]
+async fn test_legacy_get_task_with_selected_fields() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![
+        ("method", "getinfo"),
+        ("id", "dbid_1"),
+        ("additional", "transfer"),
+    ];
+    create_legacy_api_mock(&server, params, "test-files/legacy_get_tasks_success.json").await;
+
+    synods
+        .get_task_with(vec!["dbid_1".to_string()], AdditionalField::Transfer)
+        .await
+        .unwrap();
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_legacy_list_tasks_rejects_sorting() {
+    let (_server, synods) = setup_legacy_client().await;
+
+    let error = synods
+        .list_tasks()
+        .sort_by(SortField::Title, SortOrder::Ascending)
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_legacy_pause() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![("method", "pause"), ("id", "dbid_1")];
+    create_legacy_api_mock(&server, params, "test-files/legacy_operation_success.json").await;
+
+    synods.pause("dbid_1").await.unwrap();
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_legacy_resume_reports_failed_task() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![("method", "resume"), ("id", "dbid_1")];
+    create_legacy_api_mock(&server, params, "test-files/legacy_operation_failed.json").await;
+
+    let task_operation = synods.resume("dbid_1").await.unwrap();
+
+    server.verify().await;
+
+    assert_eq!(task_operation.failed_task.len(), 1);
+    assert_eq!(task_operation.failed_task[0].id, "dbid_1");
+    assert_eq!(task_operation.failed_task[0].error, 405);
+}
+
+#[tokio::test]
+async fn test_legacy_create_task() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![
+        ("method", "create"),
+        ("uri", "https://example.com/test.iso"),
+        ("destination", "downloads"),
+    ];
+    create_legacy_api_mock(&server, params, "test-files/pause_success.json").await;
+
+    synods
+        .create_task("https://example.com/test.iso", "downloads")
+        .await
+        .unwrap();
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_legacy_create_task_options() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![
+        ("method", "create"),
+        ("uri", "https://example.com/test.zip"),
+        ("unzip_password", "secret"),
+    ];
+    create_legacy_api_mock(&server, params, "test-files/pause_success.json").await;
+
+    let created = synods
+        .create_task_with(
+            "https://example.com/test.zip",
+            "downloads",
+            &CreateTaskOptions::new().extract_password("secret"),
+        )
+        .await
+        .unwrap();
+    assert!(created.task_id.is_empty());
+
+    let error = synods
+        .create_task_with(
+            "https://example.com/test.zip",
+            "downloads",
+            &CreateTaskOptions::new().create_list(true),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::InvalidInput(_))
+    ));
+
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_legacy_clear_completed_deletes_finished_tasks() {
+    let (server, synods) = setup_legacy_client().await;
+
+    create_legacy_api_mock(
+        &server,
+        vec![("method", "list")],
+        "test-files/legacy_get_tasks_success.json",
+    )
+    .await;
+
+    create_legacy_api_mock(
+        &server,
+        vec![
+            ("method", "delete"),
+            ("id", "dbid_1"),
+            ("force_complete", "false"),
+        ],
+        "test-files/legacy_operation_success.json",
+    )
+    .await;
+
+    let result = synods.clear_completed().await;
+
+    server.verify().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_legacy_get_statistics() {
+    let (server, synods) = setup_legacy_client().await;
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/DownloadStation/statistic.cgi"))
+        .and(form_param("api", "SYNO.DownloadStation.Statistic"))
+        .and(form_param("method", "getinfo"))
+        .and(form_param("_sid", "456"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file(
+                    "test-files/legacy_get_statistics_success.json",
+                )),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let statistics = synods.get_statistics().await.unwrap();
+
+    server.verify().await;
+
+    assert_eq!(statistics.speed_download, 5_242_880);
+    assert_eq!(statistics.emule_speed_download, Some(2048));
+    assert_eq!(statistics.emule_speed_upload, Some(1024));
+}
+
+#[tokio::test]
+async fn test_login_otp_required() {
+    let (server, synods) = setup_client().await;
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/otp_required.json")),
+        )
+        .mount(&server)
+        .await;
+
+    let result = synods.authorize().await;
+    assert!(result.is_err());
+
+    let err = result.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+    assert!(matches!(syno_err, SynoError::OtpRequired));
+    assert!(!synods.is_authorized().await);
+}
+
+#[tokio::test]
+async fn test_login_with_otp_remembers_device() {
+    let (server, synods) = setup_client().await;
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "login"))
+        .and(form_param("otp_code", "123456"))
+        .and(form_param("enable_device_token", "yes"))
+        .and(form_param("device_name", "syno-download-station"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/login_device_token_success.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    synods.authorize_with_otp("123456", true).await.unwrap();
+
+    server.verify().await;
+
+    assert!(synods.is_authorized().await);
+    assert_eq!(synods.device_id().await.as_deref(), Some("device_789"));
+}
+
+#[tokio::test]
+async fn test_login_with_stored_device_id() {
+    let server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
+
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .device_id("device_789")
+        .build()
+        .unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "login"))
+        .and(form_param("device_id", "device_789"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/login_success.json")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    synods.authorize().await.unwrap();
+
+    server.verify().await;
+}
+
+// Helper function to create a mock for logout
+async fn create_logout_mock(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "logout"))
+        .and(form_param("_sid", "456"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/logout_success.json")),
+        )
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_logout() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    create_logout_mock(&server).await;
+    synods.authorize().await.unwrap();
+
+    let session_info = synods.session_info().await.unwrap();
+    assert!(session_info.last_used_at >= session_info.logged_in_at);
+
+    synods.logout().await.unwrap();
+
+    server.verify().await;
+
+    assert!(!synods.is_authorized().await);
+    assert!(synods.session_info().await.is_none());
+
+    // Logging out again is a no-op
+    synods.logout().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_logout_failure_keeps_session() {
+    let mut server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
+
+    let session_file = env::temp_dir().join(format!(
+        "syno-download-station-logout-session-{}.json",
+        std::process::id()
+    ));
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .session_store(FileSessionStore::new(&session_file).unwrap())
+        .build()
+        .unwrap();
+
+    create_login_mock(&mut server).await;
+    create_logout_mock(&server).await;
+    Mock::given(method("POST"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "logout"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    synods.authorize().await.unwrap();
+
+    let error = synods.logout().await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SynoError>(),
+        Some(SynoError::Http { status: 500, .. })
+    ));
+    assert!(synods.is_authorized().await);
+    assert!(session_file.exists());
+
+    // The kept session is ended by retrying
+    synods.logout().await.unwrap();
+    assert!(!synods.is_authorized().await);
+    assert!(!session_file.exists());
+}
+
+#[tokio::test]
+async fn test_logout_on_drop_without_api_info() {
+    let server = MockServer::start().await;
+    create_logout_mock(&server).await;
+
+    // A restored session is dropped before any request queries the API info
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .session(restored_session("456"))
+        .logout_on_drop(true)
+        .build()
+        .unwrap();
+
+    drop(synods);
+
+    let mut requests = Vec::new();
+    for _ in 0..100 {
+        requests = server.received_requests().await.unwrap_or_default();
+        if !requests.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    server.verify().await;
+    assert_eq!(requests.len(), 1);
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains("method=logout"));
+    assert!(body.contains("_sid=456"));
+}
+
+#[tokio::test]
+async fn test_session_info_tracks_last_use() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
+    let logged_in = synods.session_info().await.unwrap();
+
+    let params = vec![("method", "pause"), ("id", "task_id_1")];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+    synods.pause("task_id_1").await.unwrap();
+
+    let used = synods.session_info().await.unwrap();
+    assert_eq!(used.logged_in_at, logged_in.logged_in_at);
+    assert!(used.last_used_at >= logged_in.last_used_at);
+}
+
+#[tokio::test]
+async fn test_logout_on_drop() {
+    let mut server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
+
+    let session_file = env::temp_dir().join(format!(
+        "syno-download-station-drop-session-{}.json",
+        std::process::id()
+    ));
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .logout_on_drop(true)
+        .session_store(FileSessionStore::new(&session_file).unwrap())
+        .build()
+        .unwrap();
+
+    create_login_mock(&mut server).await;
+    create_logout_mock(&server).await;
+    synods.authorize().await.unwrap();
+    assert!(session_file.exists());
+
+    drop(synods);
+
+    // The logout request is sent in the background, and the stored session is removed
+    // once the NAS has ended it
+    for _ in 0..100 {
+        if !session_file.exists() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    server.verify().await;
+    assert!(!session_file.exists());
+}
+
+#[tokio::test]
+async fn test_logout_on_drop_failure_keeps_stored_session() {
+    let mut server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
+
+    let session_file = env::temp_dir().join(format!(
+        "syno-download-station-drop-failure-session-{}.json",
+        std::process::id()
+    ));
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .logout_on_drop(true)
+        .session_store(FileSessionStore::new(&session_file).unwrap())
+        .build()
+        .unwrap();
+
+    create_login_mock(&mut server).await;
+    Mock::given(method("POST"))
+        .and(form_param("api", "SYNO.API.Auth"))
+        .and(form_param("method", "logout"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("content-type", "application/json")
+                .set_body_string(body_from_file("test-files/auth_error.json")),
         )
+        .expect(1)
         .mount(&server)
         .await;
-
-    // Re-authorize after server reset
     synods.authorize().await.unwrap();
+    assert!(session_file.exists());
+
+    drop(synods);
+
+    for _ in 0..100 {
+        let requests = server.received_requests().await.unwrap_or_default();
+        if requests
+            .iter()
+            .any(|request| String::from_utf8_lossy(&request.body).contains("method=logout"))
+        {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    // Give the background task time to handle the response
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
 
-    let tasks = synods.get_tasks().await.unwrap();
-    assert_eq!(tasks.total, 2);
+    server.verify().await;
+    // The session is still valid on the NAS, so it stays stored
+    assert!(session_file.exists());
+    std::fs::remove_file(&session_file).unwrap();
+}
+
+fn restored_session(sid: &str) -> Session {
+    Session {
+        sid: sid.into(),
+        synotoken: String::new(),
+        device_id: None,
+        obtained_at: Utc::now(),
+    }
 }
 
 #[tokio::test]
-async fn test_session_expired_reauth_fails() {
-    let (mut server, synods) = setup_client().await;
+async fn test_restored_session_is_used_without_login() {
+    let mut server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
 
-    // Initial login
-    create_login_mock(&mut server).await;
-    synods.authorize().await.unwrap();
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .session(restored_session("456"))
+        .build()
+        .unwrap();
 
-    server.reset().await;
+    assert!(synods.is_authorized().await);
+
+    let params = vec![("method", "pause"), ("id", "task_id_1")];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
+
+    synods.pause("task_id_1").await.unwrap();
+
+    // No login request was needed
+    let requests = server.received_requests().await.unwrap();
+    assert!(
+        !requests
+            .iter()
+            .any(|request| String::from_utf8_lossy(&request.body).contains("method=login"))
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_restored_session_falls_back_to_login() {
+    let mut server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
+
+    let synods = SynoDS::builder()
+        .url(server.uri())
+        .username("test")
+        .password("test123")
+        .session(restored_session("stale"))
+        .build()
+        .unwrap();
 
-    // First call returns session expired
     Mock::given(method("POST"))
         .and(path("/webapi/entry.cgi"))
-        .and(header("content-type", "application/x-www-form-urlencoded"))
-        .and(form_param("api", "SYNO.DownloadStation2.Task"))
-        .and(form_param("method", "list"))
-        .and(form_param("_sid", "456"))
+        .and(form_param("method", "pause"))
+        .and(form_param("_sid", "stale"))
         .respond_with(
             ResponseTemplate::new(200)
                 .append_header("content-type", "application/json")
                 .set_body_string(body_from_file("test-files/session_expired.json")),
         )
-        .up_to_n_times(1)
+        .expect(1)
         .mount(&server)
         .await;
+    create_login_mock(&mut server).await;
+    let params = vec![("method", "pause"), ("id", "task_id_1")];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    // Re-auth fails (returns failure)
-    Mock::given(method("POST"))
-        .and(path("/webapi/entry.cgi"))
-        .and(header("content-type", "application/x-www-form-urlencoded"))
-        .and(form_param("api", "SYNO.API.Auth"))
-        .and(form_param("method", "login"))
-        .respond_with(
-            ResponseTemplate::new(200)
-                .append_header("content-type", "application/json")
-                .set_body_string(r#"{"success": false}"#),
-        )
-        .mount(&server)
-        .await;
+    synods.pause("task_id_1").await.unwrap();
 
-    let result = synods.get_tasks().await;
-    assert!(result.is_err());
+    server.verify().await;
+
+    assert_eq!(synods.export_session().await.unwrap().sid, "456");
 }
 
-#[tokio::test]
-async fn test_api_error_preserves_code() {
-    let (mut server, synods) = setup_client().await;
+#[test]
+fn test_session_debug_redacts_credentials() {
+    let session = restored_session("secret-sid");
+    let debug = format!("{session:?}");
+    assert!(!debug.contains("secret-sid"));
+    assert!(debug.contains("obtained_at"));
+}
 
+#[cfg(unix)]
+#[test]
+fn test_file_session_store_restricts_existing_file() {
+    use std::os::unix::fs::PermissionsExt;
+    use syno_download_station::session::SessionStore;
+
+    let session_file = env::temp_dir().join(format!(
+        "syno-download-station-permissions-{}.json",
+        std::process::id()
+    ));
+    fs::write(&session_file, "{}").unwrap();
+    fs::set_permissions(&session_file, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let store = FileSessionStore::new(&session_file).unwrap();
+    store.save(&restored_session("456")).unwrap();
+
+    let mode = fs::metadata(&session_file).unwrap().permissions().mode();
+    store.clear().unwrap();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[tokio::test]
+async fn test_file_session_store() {
+    let mut server = MockServer::start().await;
+    create_api_info_mock(&server, "test-files/api_info_success.json").await;
     create_login_mock(&mut server).await;
+    create_logout_mock(&server).await;
+
+    let session_file = env::temp_dir().join(format!(
+        "syno-download-station-session-{}.json",
+        std::process::id()
+    ));
+    let build_client = || {
+        SynoDS::builder()
+            .url(server.uri())
+            .username("test")
+            .password("test123")
+            .session_store(FileSessionStore::new(&session_file).unwrap())
+            .build()
+            .unwrap()
+    };
+
+    // Login saves the session
+    let synods = build_client();
+    assert!(!synods.is_authorized().await);
     synods.authorize().await.unwrap();
+    assert!(session_file.exists());
 
-    let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "list"),
-        (
-            "additional",
-            r#"["transfer","tracker","peer","file","detail"]"#,
-        ),
-    ];
-
-    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+    // A new client restores it
+    let restored = build_client();
+    assert!(restored.is_authorized().await);
+    let session = restored.export_session().await.unwrap();
+    assert_eq!(session.sid, "456");
+    assert_eq!(session.synotoken, "--------");
 
-    let result = synods.get_tasks().await;
-    assert!(result.is_err());
+    // Logout removes it
+    restored.logout().await.unwrap();
+    assert!(!session_file.exists());
 
-    let err = result.unwrap_err();
-    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
-    match syno_err {
-        SynoError::Api { code, .. } => assert_eq!(*code, 403),
-        other => panic!("Expected SynoError::Api, got: {other:?}"),
-    }
+    server.verify().await;
 }
 
 #[tokio::test]
-async fn test_session_expired_file_upload_retry() {
+async fn test_api_error_code_is_decoded() {
     let (mut server, synods) = setup_client().await;
 
-    // Initial login
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    server.reset().await;
+    let params = vec![("method", "pause"), ("id", "task_id_1")];
+    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+
+    let err = synods.pause("task_id_1").await.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+
+    assert_eq!(syno_err.error_code(), Some(ErrorCode::DestinationNotFound));
+    assert!(!syno_err.is_retryable());
+    assert!(!syno_err.is_permission_denied());
+    assert_eq!(
+        syno_err.to_string(),
+        "Synology API error: code=403 (Destination does not exist), message=Failed to pause task"
+    );
+}
+
+#[tokio::test]
+async fn test_http_error_is_retryable() {
+    let (mut server, synods) = setup_client().await;
 
-    // Re-mount login mock for re-auth
     create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
 
-    // First file upload returns session expired
     Mock::given(method("POST"))
         .and(path("/webapi/entry.cgi"))
-        .and(header_regex("content-type", "multipart/form-data"))
-        .respond_with(
-            ResponseTemplate::new(200)
-                .append_header("content-type", "application/json")
-                .set_body_string(body_from_file("test-files/session_expired.json")),
-        )
-        .up_to_n_times(1)
+        .and(form_param("method", "pause"))
+        .respond_with(ResponseTemplate::new(503))
         .mount(&server)
         .await;
 
-    // Retry succeeds
-    Mock::given(method("POST"))
-        .and(path("/webapi/entry.cgi"))
-        .and(header_regex("content-type", "multipart/form-data"))
-        .respond_with(
-            ResponseTemplate::new(200)
-                .append_header("content-type", "application/json")
-                .set_body_string(body_from_file(
-                    "test-files/create_task_from_file_success.json",
-                )),
-        )
-        .mount(&server)
-        .await;
+    let err = synods.pause("task_id_1").await.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
 
-    let file_data = fs::read("test-files/test.torrent").expect("Failed to read test file");
-    let result = synods
-        .create_task_from_file(&file_data, "test.torrent", "/downloads")
-        .await;
+    match syno_err {
+        SynoError::Http { status: 503, .. } => {}
+        other => panic!("Expected SynoError::Http with status 503, got: {other:?}"),
+    }
+    assert!(syno_err.is_retryable());
+    assert_eq!(syno_err.error_code(), None);
+}
 
-    assert!(result.is_ok());
+#[tokio::test]
+async fn test_network_error_is_retryable() {
+    // Reserve a free port, then close it so the connection is refused
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let synods = SynoDS::builder()
+        .url(url)
+        .username("test")
+        .password("test123")
+        .build()
+        .unwrap();
+
+    let err = synods.authorize().await.unwrap_err();
+    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+
+    match syno_err {
+        SynoError::Network(error) => assert!(error.is_connect()),
+        other => panic!("Expected SynoError::Network, got: {other:?}"),
+    }
+    assert!(syno_err.is_retryable());
 }
 
 #[tokio::test]
-async fn test_auth_error_preserves_code() {
-    let (server, synods) = setup_client().await;
+async fn test_malformed_response_is_not_retryable() {
+    let (mut server, synods) = setup_client().await;
+
+    create_login_mock(&mut server).await;
+    synods.authorize().await.unwrap();
 
     Mock::given(method("POST"))
         .and(path("/webapi/entry.cgi"))
-        .and(header("content-type", "application/x-www-form-urlencoded"))
-        .and(form_param("api", "SYNO.API.Auth"))
-        .and(form_param("method", "login"))
-        .respond_with(
-            ResponseTemplate::new(200)
-                .append_header("content-type", "application/json")
-                .set_body_string(body_from_file("test-files/auth_error.json")),
-        )
+        .and(form_param("method", "pause"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<html>Not JSON</html>"))
         .mount(&server)
         .await;
 
-    let result = synods.authorize().await;
-    assert!(result.is_err());
-
-    let err = result.unwrap_err();
+    let err = synods.pause("task_id_1").await.unwrap_err();
     let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+
     match syno_err {
-        SynoError::Auth {
-            code: Some(400), ..
-        } => {}
-        other => panic!("Expected SynoError::Auth with code 400, got: {other:?}"),
+        SynoError::InvalidResponse(_) => {}
+        other => panic!("Expected SynoError::InvalidResponse, got: {other:?}"),
     }
+    assert!(!syno_err.is_retryable());
 }
 
 #[tokio::test]
-async fn test_create_task_error_preserves_code() {
+async fn test_file_upload_http_error() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "create"),
-        ("type", "\"url\""),
-        ("destination", "/downloads"),
-        ("url", "https://example.com/test.iso"),
-        ("create_list", "false"),
-    ];
-
-    create_api_mock(&mut server, params, "test-files/api_error.json").await;
-
-    let result = synods
-        .create_task("https://example.com/test.iso", "/downloads")
+    Mock::given(method("POST"))
+        .and(path("/webapi/entry.cgi"))
+        .and(header_regex("content-type", "multipart/form-data"))
+        .respond_with(ResponseTemplate::new(502))
+        .mount(&server)
         .await;
-    assert!(result.is_err());
 
-    let err = result.unwrap_err();
+    let file_data = fs::read("test-files/test.torrent").expect("Failed to read test file");
+    let err = synods
+        .create_task_from_file(&file_data, "test.torrent", "/downloads")
+        .await
+        .unwrap_err();
     let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
+
     match syno_err {
-        SynoError::Api { code: 403, .. } => {}
-        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
+        SynoError::Http { status: 502, .. } => {}
+        other => panic!("Expected SynoError::Http with status 502, got: {other:?}"),
     }
+    assert!(syno_err.is_retryable());
 }
 
 #[tokio::test]
-async fn test_resume_error_preserves_code() {
+async fn test_resume_tasks_reports_failed_tasks() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
@@ -631,74 +3244,98 @@ async fn test_resume_error_preserves_code() {
 
     let params = vec![
         ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
         ("method", "resume"),
-        ("id", "task_id_1"),
+        ("id", "task_id_1,task_id_2"),
     ];
+    create_api_mock(&mut server, params, "test-files/batch_partial_success.json").await;
 
-    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+    let result = synods
+        .resume_tasks(&["task_id_1", "task_id_2"])
+        .await
+        .unwrap();
 
-    let result = synods.resume("task_id_1").await;
-    assert!(result.is_err());
+    server.verify().await;
 
-    let err = result.unwrap_err();
-    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
-    match syno_err {
-        SynoError::Api { code: 403, .. } => {}
-        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
-    }
+    assert!(!result.is_success());
+    assert_eq!(result.succeeded, vec!["task_id_1"]);
+    assert_eq!(result.failed.len(), 1);
+    assert_eq!(result.failed[0].id, "task_id_2");
+    assert_eq!(result.failed[0].code, 405);
+    assert_eq!(result.failed[0].error, ErrorCode::InvalidTaskAction);
 }
 
 #[tokio::test]
-async fn test_delete_task_error_preserves_code() {
+async fn test_delete_tasks_error_lists_failed_tasks() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "delete"),
-        ("id", "task_id_1"),
-    ];
-
-    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+    let params = vec![("method", "delete"), ("id", "task_id_1,task_id_2")];
+    create_api_mock(&mut server, params, "test-files/batch_all_failed.json").await;
 
-    let result = synods.delete_task("task_id_1", false).await;
-    assert!(result.is_err());
+    let result = synods
+        .delete_tasks(&["task_id_1", "task_id_2"], false)
+        .await
+        .unwrap();
 
-    let err = result.unwrap_err();
-    let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
-    match syno_err {
-        SynoError::Api { code: 403, .. } => {}
-        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
-    }
+    // Like `delete_task`, `force_complete` is only sent when set
+    let requests = server.received_requests().await.unwrap();
+    let delete_request = requests
+        .iter()
+        .find(|request| String::from_utf8_lossy(&request.body).contains("method=delete"))
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&delete_request.body).contains("force_complete"));
+
+    assert!(result.succeeded.is_empty());
+    assert_eq!(result.failed.len(), 2);
+    assert!(
+        result
+            .failed
+            .iter()
+            .all(|failed| failed.error == ErrorCode::InvalidTaskId)
+    );
 }
 
 #[tokio::test]
-async fn test_clear_completed_error_preserves_code() {
+async fn test_pause_tasks_without_failures() {
     let (mut server, synods) = setup_client().await;
 
     create_login_mock(&mut server).await;
     synods.authorize().await.unwrap();
 
-    let params = vec![
-        ("api", "SYNO.DownloadStation2.Task"),
-        ("version", "2"),
-        ("method", "delete_condition"),
-        ("status", "5"),
-    ];
+    let params = vec![("method", "pause"), ("id", "task_id_1,task_id_2")];
+    create_api_mock(&mut server, params, "test-files/pause_success.json").await;
 
-    create_api_mock(&mut server, params, "test-files/api_error.json").await;
+    let result = synods
+        .pause_tasks(&["task_id_1", "task_id_2"])
+        .await
+        .unwrap();
 
-    let result = synods.clear_completed().await;
-    assert!(result.is_err());
+    assert!(result.is_success());
+    assert_eq!(result.succeeded, vec!["task_id_1", "task_id_2"]);
+}
 
-    let err = result.unwrap_err();
+#[tokio::test]
+async fn test_batch_operation_requires_ids() {
+    let (_server, synods) = setup_client().await;
+
+    let err = synods.complete_tasks(&[]).await.unwrap_err();
     let syno_err = err.downcast_ref::<SynoError>().expect("expected SynoError");
-    match syno_err {
-        SynoError::Api { code: 403, .. } => {}
-        other => panic!("Expected SynoError::Api with code 403, got: {other:?}"),
-    }
+    assert!(matches!(syno_err, SynoError::InvalidInput(_)));
+}
+
+#[tokio::test]
+async fn test_legacy_pause_tasks() {
+    let (server, synods) = setup_legacy_client().await;
+
+    let params = vec![("method", "pause"), ("id", "dbid_1,dbid_2")];
+    create_legacy_api_mock(&server, params, "test-files/legacy_operation_failed.json").await;
+
+    let result = synods.pause_tasks(&["dbid_1", "dbid_2"]).await.unwrap();
+
+    server.verify().await;
+
+    assert_eq!(result.succeeded, vec!["dbid_2"]);
+    assert_eq!(result.failed[0].id, "dbid_1");
 }